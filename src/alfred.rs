@@ -0,0 +1,110 @@
+//! [ALFRED](https://alfred.stlouisfed.org/) style requests for a series as it was known at points
+//! in the past, built on `series/vintagedates` and `series/observations`.
+//!
+//! ```
+//! let id = SeriesId::new("GDP")?;
+//! let as_known = FredClient::series_observations_as_of(&id, "2009-01-01")?;
+//! let vintages = FredClient::series_vintages(&id)?;
+//! let first = FredClient::series_first_release(&id)?;
+//! ```
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::{
+    req,
+    FredClient,
+    FredRequest,
+    SeriesId,
+    SeriesObservations,
+    VintageMatrix,
+    VintageObservations,
+    VintageOutput,
+};
+
+// The number of vintage dates sent in one request, to keep the URL to a reasonable length.
+const VINTAGE_DATES_PER_REQUEST: usize = 200;
+
+/// The first published value of an observation.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FirstRelease {
+    pub date:                       String,
+    pub vintage_date:               String,
+    pub value:                      String,
+}
+
+impl FredClient {
+    /// The observations of a series as they were known on `date`.
+    pub fn series_observations_as_of(series_id: &SeriesId, date: &str) -> Result<SeriesObservations> {
+        req(FredRequest::new(
+            "series/observations",
+            vec![
+                ("series_id", series_id.to_string()),
+                ("realtime_start", date.to_string()),
+                ("realtime_end", date.to_string()),
+            ],
+        )?)
+    }
+
+    /// The observations of a series for a set of vintage dates, in a single request.
+    pub fn series_observations_for_vintages(
+        series_id: &SeriesId,
+        output_type: VintageOutput,
+        vintage_dates: &[String]) -> Result<VintageObservations>
+    {
+        req(FredRequest::new(
+            "series/observations",
+            vec![
+                ("series_id", series_id.to_string()),
+                ("output_type", output_type.code().to_string()),
+                ("vintage_dates", vintage_dates.join(",")),
+            ],
+        )?)
+    }
+
+    /// Every vintage of a series, driven by `series_vintagedates()`. Cells hold new and revised
+    /// values only (output_type 3), so use `VintageMatrix::as_of()` to get the full set of
+    /// observations in a vintage.
+    pub fn series_vintages(series_id: &SeriesId) -> Result<VintageMatrix> {
+        let vintage_dates = FredClient::series_vintagedates(series_id)?.vintage_dates;
+        FredClient::series_vintages_for(series_id, &vintage_dates)
+    }
+
+    /// As `series_vintages()` but for the given vintage dates only, split over as many requests as
+    /// needed.
+    pub fn series_vintages_for(series_id: &SeriesId, vintage_dates: &[String]) -> Result<VintageMatrix> {
+        let mut matrix: Option<VintageMatrix> = None;
+        for chunk in vintage_dates.chunks(VINTAGE_DATES_PER_REQUEST) {
+            let obs = FredClient::series_observations_for_vintages(
+                series_id,
+                VintageOutput::NewAndRevised,
+                chunk,
+            )?;
+            matrix = Some(match matrix {
+                Some(m) => m.merge(obs.observations),
+                None => obs.observations,
+            });
+        }
+        Ok(matrix.unwrap_or_else(VintageMatrix::empty))
+    }
+
+    /// The first published value for each observation date of a series.
+    pub fn series_first_release(series_id: &SeriesId) -> Result<Vec<FirstRelease>> {
+        Ok(FredClient::series_vintages(series_id)?.first_releases())
+    }
+}
+
+impl VintageMatrix {
+    /// The first published value for each observation date.
+    pub fn first_releases(&self) -> Vec<FirstRelease> {
+        self.initial_release()
+            .into_iter()
+            .map(|(date, vintage_date, value)| {
+                FirstRelease {
+                    date:           date.to_string(),
+                    vintage_date:   vintage_date.to_string(),
+                    value:          value.to_string(),
+                }
+            })
+            .collect()
+    }
+}
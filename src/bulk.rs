@@ -0,0 +1,250 @@
+//! The FRED v2 bulk endpoint, which returns observations for every series on a release. Mirroring
+//! a whole release this way replaces one `series_observations()` request per series.
+//!
+//! ```
+//! for block in FredClient::release_observations(ReleaseId::new(50)).observation_start("2020-01-01") {
+//!     let block = block?;
+//!     println!("{} {}", block.series_id, block.observations.len());
+//! }
+//! ```
+
+use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+};
+use crate::{
+    req,
+    FredClient,
+    FredRequest,
+    IntoRequest,
+    ReleaseId,
+    SeriesId,
+};
+
+/// A request to the FRED v2 API. The API key is sent as a bearer token rather than a parameter.
+#[derive(Debug)]
+pub struct V2Request(FredRequest);
+
+impl V2Request {
+    pub fn new<T: Display>(url: &str, keyvals: Vec<(&'static str, T)>) -> Result<Self> {
+        Ok(V2Request(FredRequest::new(url, keyvals)?))
+    }
+}
+
+impl IntoRequest for V2Request {
+    fn into_request(&self) -> Result<String> {
+        Ok(
+            format!(
+                "{}fred/v2/{}?{}",
+                self.base_url()?,
+                self.0.url,
+                self.0.concat_keyvals('&'),
+            )
+        )
+    }
+
+    fn base_url(&self) -> Result<String> {
+        self.0.base_url()
+    }
+
+    fn api_key(&self) -> Result<String> {
+        self.0.api_key()
+    }
+
+    fn bearer_token(&self) -> Result<Option<String>> {
+        Ok(Some(self.api_key()?))
+    }
+}
+
+impl FredClient {
+    /// Get observations for all series on a release, from the v2 `release/observations` endpoint.
+    /// Pages are fetched as the iterator is consumed.
+    pub fn release_observations(release_id: ReleaseId) -> ReleaseObservationsIter {
+        ReleaseObservationsIter::new(release_id)
+    }
+}
+
+type FetchPage = Box<dyn FnMut(Option<&str>) -> Result<ReleaseObservationsPage>>;
+
+/// An iterator over the series blocks of a release, following `next_cursor` until there are no
+/// more pages. A series split across two pages is yielded as a single block.
+pub struct ReleaseObservationsIter {
+    release_id:         ReleaseId,
+    params:             Vec<(&'static str, String)>,
+    fetch:              Option<FetchPage>,
+    cursor:             Option<String>,
+    ready:              VecDeque<SeriesBlock>,
+    // The last block of the latest page, held back in case it continues on the next page.
+    held:               Option<SeriesBlock>,
+    done:               bool,
+}
+
+impl ReleaseObservationsIter {
+    fn new(release_id: ReleaseId) -> Self {
+        ReleaseObservationsIter {
+            release_id,
+            params:     Vec::new(),
+            fetch:      None,
+            cursor:     None,
+            ready:      VecDeque::new(),
+            held:       None,
+            done:       false,
+        }
+    }
+
+    pub fn observation_start(mut self, date: &str) -> Self {
+        self.params.push(("observation_start", date.to_string()));
+        self
+    }
+
+    pub fn observation_end(mut self, date: &str) -> Self {
+        self.params.push(("observation_end", date.to_string()));
+        self
+    }
+
+    /// The maximum number of observations per page.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.params.push(("limit", limit.to_string()));
+        self
+    }
+
+    fn fetch_page(&mut self) -> Result<ReleaseObservationsPage> {
+        let cursor = self.cursor.clone();
+        if let Some(fetch) = &mut self.fetch {
+            return fetch(cursor.as_deref())
+        }
+        let mut keyvals = vec![("release_id", self.release_id.to_string())];
+        keyvals.extend(self.params.iter().cloned());
+        if let Some(cursor) = cursor {
+            keyvals.push(("next_cursor", cursor));
+        }
+        req(V2Request::new("release/observations", keyvals)?)
+    }
+
+    fn push_page(&mut self, page: ReleaseObservationsPage) {
+        let has_more = page.has_more;
+        self.cursor = page.next_cursor.filter(|_| has_more);
+        self.done = self.cursor.is_none();
+
+        for block in page.series {
+            match self.held.take() {
+                Some(mut held) if held.series_id == block.series_id => {
+                    held.observations.extend(block.observations);
+                    self.held = Some(held);
+                },
+                Some(held) => {
+                    self.ready.push_back(held);
+                    self.held = Some(block);
+                },
+                None => self.held = Some(block),
+            }
+        }
+        if self.done {
+            self.ready.extend(self.held.take());
+        }
+    }
+}
+
+impl Iterator for ReleaseObservationsIter {
+    type Item = Result<SeriesBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() && !self.done {
+            match self.fetch_page() {
+                Ok(page) => self.push_page(page),
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err))
+                },
+            }
+        }
+        self.ready.pop_front().map(Ok)
+    }
+}
+
+// Response data-structures ///////////////////////////////////////////////////////////////////////
+
+/// A page of the v2 `release/observations` endpoint.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReleaseObservationsPage {
+    #[serde(default)]
+    pub release:                    Option<Value>,
+    pub series:                     Vec<SeriesBlock>,
+    #[serde(default)]
+    pub has_more:                   bool,
+    #[serde(default)]
+    pub next_cursor:                Option<String>,
+}
+
+/// The observations of one series on a release.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SeriesBlock {
+    pub series_id:                  SeriesId,
+    #[serde(default)]
+    pub title:                      Option<String>,
+    #[serde(default)]
+    pub frequency:                  Option<String>,
+    #[serde(default)]
+    pub units:                      Option<String>,
+    #[serde(default)]
+    pub seasonal_adjustment:        Option<String>,
+    #[serde(default)]
+    pub last_updated:               Option<String>,
+    pub observations:               Vec<BulkObservation>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BulkObservation {
+    pub date:                       String,
+    /// As in `Observation`, a missing value is `"."`.
+    #[serde(deserialize_with = "value_or_missing")]
+    pub value:                      String,
+}
+
+// v2 values may be numbers, strings or null.
+fn value_or_missing<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Null => Ok(".".into()),
+        other => Err(serde::de::Error::custom(format!("Expected observation value, found [{}].", other))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ReleaseId, ReleaseObservationsIter, ReleaseObservationsPage};
+
+    const PAGES: [&str; 2] = [
+        r#"{"has_more": true, "next_cursor": "abc", "series": [
+            {"series_id": "AAA", "observations": [{"date": "2020-01-01", "value": 1.5}]},
+            {"series_id": "BBB", "observations": [{"date": "2020-01-01", "value": "2.0"}]}
+        ]}"#,
+        r#"{"has_more": false, "series": [
+            {"series_id": "BBB", "observations": [{"date": "2020-02-01", "value": null}]},
+            {"series_id": "CCC", "observations": []}
+        ]}"#,
+    ];
+
+    #[test]
+    fn pages_follow_cursor_and_merge_split_series() {
+        let mut cursors = Vec::new();
+        let mut iter = ReleaseObservationsIter::new(ReleaseId::new(50));
+        iter.fetch = Some(Box::new(move |cursor: Option<&str>| {
+            cursors.push(cursor.map(String::from));
+            let page = if cursor.is_none() { PAGES[0] } else { PAGES[1] };
+            assert!(cursors.len() <= 2);
+            Ok(serde_json::from_str::<ReleaseObservationsPage>(page)?)
+        }));
+
+        let blocks: Vec<_> = iter.map(|block| block.unwrap()).collect();
+        let ids: Vec<&str> = blocks.iter().map(|b| b.series_id.as_str()).collect();
+        assert_eq!(ids, vec!["AAA", "BBB", "CCC"]);
+        assert_eq!(blocks[0].observations[0].value, "1.5");
+        assert_eq!(blocks[1].observations.len(), 2);
+        assert_eq!(blocks[1].observations[1].value, ".");
+    }
+}
@@ -0,0 +1,235 @@
+//! A release calendar built on `releases/dates` and `release/dates`. Scheduled dates are only
+//! returned with `include_release_dates_with_no_data`, so the calendar sets it by default.
+//!
+//! ```
+//! let calendar = FredClient::release_calendar(&CalendarQuery::between("2024-06-01", "2024-06-30"))?;
+//! for (day, releases) in calendar.by_day() {
+//!     println!("{}: {}", day, releases.iter().map(|r| r.release_name.as_str()).collect::<Vec<_>>().join(", "));
+//! }
+//! let next_gdp = FredClient::next_release(ReleaseId::new(53), "2024-06-01")?;
+//! ```
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::{
+    req,
+    FredClient,
+    FredRequest,
+    ReleaseDate,
+    ReleaseDates,
+    ReleaseId,
+    ReleaseItem,
+    ReleasesDates,
+};
+
+mod ics;
+
+// The maximum `limit` of `releases/dates` and `release/dates`.
+const DATES_PAGE_LIMIT: usize = 1000;
+
+/// The date range and options of a calendar request.
+#[derive(Clone, Debug)]
+pub struct CalendarQuery {
+    start:          String,
+    end:            String,
+    with_no_data:   bool,
+    descending:     bool,
+}
+
+impl CalendarQuery {
+    /// Release dates from `start` to `end` inclusive, as `YYYY-MM-DD`.
+    pub fn between(start: &str, end: &str) -> Self {
+        CalendarQuery {
+            start:          start.to_string(),
+            end:            end.to_string(),
+            with_no_data:   true,
+            descending:     false,
+        }
+    }
+
+    /// Release dates from `start` onwards, including all scheduled dates.
+    pub fn from(start: &str) -> Self {
+        CalendarQuery::between(start, "9999-12-31")
+    }
+
+    /// Include dates for which no data has been released yet. Defaults to true, which is needed
+    /// for future dates.
+    pub fn include_release_dates_with_no_data(mut self, include: bool) -> Self {
+        self.with_no_data = include;
+        self
+    }
+
+    /// Latest dates first.
+    pub fn descending(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+
+    fn keyvals(&self, limit: usize, offset: usize) -> Vec<(&'static str, String)> {
+        vec![
+            ("realtime_start", self.start.clone()),
+            ("realtime_end", self.end.clone()),
+            ("include_release_dates_with_no_data", self.with_no_data.to_string()),
+            ("order_by", "release_date".to_string()),
+            ("sort_order", if self.descending { "desc" } else { "asc" }.to_string()),
+            ("limit", limit.to_string()),
+            ("offset", offset.to_string()),
+        ]
+    }
+}
+
+impl FredClient {
+    /// The release dates of all releases in the range of `query`.
+    pub fn release_calendar(query: &CalendarQuery) -> Result<ReleaseCalendar> {
+        let mut dates = Vec::new();
+        loop {
+            let page: ReleasesDates = req(FredRequest::new("releases/dates", query.keyvals(DATES_PAGE_LIMIT, dates.len()))?)?;
+            let fetched = page.release_dates.len();
+            dates.extend(page.release_dates);
+            if fetched == 0 || dates.len() as isize >= page.count {
+                break
+            }
+        }
+        Ok(ReleaseCalendar { dates })
+    }
+
+    /// The release dates of one release in the range of `query`, with the release name.
+    pub fn release_calendar_for(release_id: ReleaseId, query: &CalendarQuery) -> Result<ReleaseCalendar> {
+        let name = release_name(release_id)?;
+        let mut dates = Vec::new();
+        loop {
+            let mut keyvals = query.keyvals(DATES_PAGE_LIMIT, dates.len());
+            keyvals.push(("release_id", release_id.to_string()));
+            let page: ReleaseDates = req(FredRequest::new("release/dates", keyvals)?)?;
+            let fetched = page.release_dates.len();
+            dates.extend(page.release_dates.into_iter().map(|item| {
+                ReleaseDate { release_id: item.release_id, release_name: name.clone(), date: item.date }
+            }));
+            if fetched == 0 || dates.len() as isize >= page.count {
+                break
+            }
+        }
+        Ok(ReleaseCalendar { dates })
+    }
+
+    /// The first release date of `release_id` on or after `date`, or `None` if none is
+    /// scheduled.
+    pub fn next_release(release_id: ReleaseId, date: &str) -> Result<Option<ReleaseDate>> {
+        let mut keyvals = CalendarQuery::from(date).keyvals(1, 0);
+        keyvals.push(("release_id", release_id.to_string()));
+        let page: ReleaseDates = req(FredRequest::new("release/dates", keyvals)?)?;
+        match page.release_dates.into_iter().next() {
+            Some(item) => {
+                let release_name = release_name(release_id)?;
+                Ok(Some(ReleaseDate { release_id: item.release_id, release_name, date: item.date }))
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+fn release_name(release_id: ReleaseId) -> Result<String> {
+    FredClient::release(release_id)?
+        .releases
+        .into_iter()
+        .next()
+        .map(|release| release.name)
+        .ok_or_else(|| anyhow!("Release [{}] was not found.", release_id))
+}
+
+/// Release dates in date order.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ReleaseCalendar {
+    pub dates:                      Vec<ReleaseDate>,
+}
+
+impl ReleaseCalendar {
+    pub fn new(mut dates: Vec<ReleaseDate>) -> Self {
+        dates.sort_by(|a, b| a.date.cmp(&b.date).then(a.release_id.cmp(&b.release_id)));
+        ReleaseCalendar { dates }
+    }
+
+    /// The release dates from `start` to `end` inclusive.
+    pub fn between(&self, start: &str, end: &str) -> ReleaseCalendar {
+        ReleaseCalendar {
+            dates: self.dates
+                .iter()
+                .filter(|d| d.date.as_str() >= start && d.date.as_str() <= end)
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Releases grouped by day.
+    pub fn by_day(&self) -> BTreeMap<&str, Vec<&ReleaseDate>> {
+        let mut days: BTreeMap<&str, Vec<&ReleaseDate>> = BTreeMap::new();
+        for date in &self.dates {
+            days.entry(date.date.as_str()).or_default().push(date);
+        }
+        days
+    }
+
+    /// The first release date of `release_id` on or after `date`.
+    pub fn next(&self, release_id: ReleaseId, date: &str) -> Option<&ReleaseDate> {
+        self.dates
+            .iter()
+            .filter(|d| d.release_id == release_id && d.date.as_str() >= date)
+            .min_by(|a, b| a.date.cmp(&b.date))
+    }
+
+    /// Each release date with its release, for the press release flag and link. A date whose
+    /// release is not in `releases` is paired with `None`.
+    pub fn join<'a>(&'a self, releases: &'a [ReleaseItem]) -> Vec<(&'a ReleaseDate, Option<&'a ReleaseItem>)> {
+        self.dates
+            .iter()
+            .map(|date| (date, releases.iter().find(|release| release.id == date.release_id)))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        calendar::DATES_PAGE_LIMIT,
+        CalendarQuery,
+        ReleaseCalendar,
+        ReleaseDate,
+        ReleaseId,
+    };
+
+    fn release_date(id: usize, name: &str, date: &str) -> ReleaseDate {
+        ReleaseDate { release_id: ReleaseId::new(id), release_name: name.into(), date: date.into() }
+    }
+
+    #[test]
+    fn calendar_groups_and_finds_next_release() {
+        let calendar = ReleaseCalendar::new(vec![
+            release_date(53, "Gross Domestic Product", "2024-06-27"),
+            release_date(50, "Employment Situation", "2024-06-07"),
+            release_date(10, "Consumer Price Index", "2024-06-12"),
+            release_date(21, "H.6 Money Stock Measures", "2024-06-27"),
+            release_date(53, "Gross Domestic Product", "2024-07-25"),
+        ]);
+
+        let days = calendar.by_day();
+        assert_eq!(days.keys().copied().collect::<Vec<_>>(), vec!["2024-06-07", "2024-06-12", "2024-06-27", "2024-07-25"]);
+        assert_eq!(days["2024-06-27"].len(), 2);
+
+        assert_eq!(calendar.next(ReleaseId::new(53), "2024-06-28").unwrap().date, "2024-07-25");
+        assert!(calendar.next(ReleaseId::new(50), "2024-06-08").is_none());
+        assert_eq!(calendar.between("2024-06-10", "2024-06-30").len(), 3);
+
+        let keyvals = CalendarQuery::between("2024-06-01", "2024-06-30").keyvals(DATES_PAGE_LIMIT, 0);
+        assert!(keyvals.contains(&("include_release_dates_with_no_data", "true".to_string())));
+        assert!(keyvals.contains(&("realtime_end", "2024-06-30".to_string())));
+    }
+}
@@ -0,0 +1,147 @@
+//! iCalendar (RFC 5545) export of a release calendar, one all-day event per release date.
+//!
+//! ```
+//! let releases = FredClient::releases()?.releases;
+//! let calendar = FredClient::release_calendar(&CalendarQuery::from("2024-01-01"))?
+//!     .only(&[ReleaseId::new(10), ReleaseId::new(53)]);
+//! calendar.write_ics("fred.ics", &releases)?;
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use std::{fs, path::Path};
+use crate::{ReleaseCalendar, ReleaseId, ReleaseItem};
+
+impl ReleaseCalendar {
+    /// Only the dates of the releases in `release_ids`.
+    pub fn only(&self, release_ids: &[ReleaseId]) -> ReleaseCalendar {
+        ReleaseCalendar {
+            dates: self.dates
+                .iter()
+                .filter(|d| release_ids.contains(&d.release_id))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// The calendar as an `.ics` document. Each event is described by the release name, and the
+    /// link and press release flag of the release from `releases`. Dates that fail to parse are
+    /// skipped.
+    pub fn to_ics(&self, releases: &[ReleaseItem]) -> String {
+        self.to_ics_stamped(releases, &Utc::now().format("%Y%m%dT%H%M%SZ").to_string())
+    }
+
+    pub fn write_ics<P: AsRef<Path>>(&self, path: P, releases: &[ReleaseItem]) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_ics(releases)).context(format!("Failed to write [{}]", path.display()))
+    }
+
+    fn to_ics_stamped(&self, releases: &[ReleaseItem], stamp: &str) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//fred_api//FRED release calendar//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            "X-WR-CALNAME:FRED releases".to_string(),
+        ];
+        for (date, release) in self.join(releases) {
+            let day = match NaiveDate::parse_from_str(&date.date, "%Y-%m-%d") {
+                Ok(day) => day,
+                Err(_) => continue,
+            };
+            // Without the release, whether it has a press release is not known.
+            let mut description = date.release_name.clone();
+            if let Some(release) = release {
+                let press_release = if release.press_release { "yes" } else { "no" };
+                description.push_str(&format!("\nPress release: {}", press_release));
+            }
+            let link = release.and_then(|r| r.link.as_deref());
+            if let Some(link) = link {
+                description.push_str(&format!("\nLink: {}", link));
+            }
+
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:release-{}-{}@fred.stlouisfed.org", date.release_id, day.format("%Y%m%d")));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+            lines.push(format!("DTEND;VALUE=DATE:{}", (day + Duration::days(1)).format("%Y%m%d")));
+            lines.push(format!("SUMMARY:{}", escape(&date.release_name)));
+            lines.push(format!("DESCRIPTION:{}", escape(&description)));
+            if let Some(link) = link {
+                lines.push(format!("URL:{}", link));
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        let mut ics = String::new();
+        for line in lines {
+            ics.push_str(&fold(&line));
+            ics.push_str("\r\n");
+        }
+        ics
+    }
+}
+
+// Escape a TEXT value.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Fold a content line longer than 75 octets, without splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ReleaseCalendar, ReleaseDate, ReleaseId, ReleaseItem};
+
+    #[test]
+    fn ics_has_one_event_per_selected_release_date() {
+        let calendar = ReleaseCalendar::new(vec![
+            ReleaseDate { release_id: ReleaseId::new(10), release_name: "Consumer Price Index".into(), date: "2024-06-12".into() },
+            ReleaseDate { release_id: ReleaseId::new(21), release_name: "H.6 Money Stock Measures".into(), date: "2024-06-25".into() },
+            ReleaseDate { release_id: ReleaseId::new(53), release_name: "Gross Domestic Product, Advance".into(), date: "2024-07-25".into() },
+        ]);
+        let releases = vec![
+            ReleaseItem {
+                id:             ReleaseId::new(53),
+                realtime_start: "2024-06-01".into(),
+                realtime_end:   "2024-06-01".into(),
+                name:           "Gross Domestic Product".into(),
+                press_release:  true,
+                link:           Some("https://www.bea.gov/".into()),
+            },
+        ];
+
+        let ics = calendar
+            .only(&[ReleaseId::new(10), ReleaseId::new(53)])
+            .to_ics_stamped(&releases, "20240601T000000Z");
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240725\r\nDTEND;VALUE=DATE:20240726\r\n"));
+        assert!(ics.contains("SUMMARY:Gross Domestic Product\\, Advance\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= 75));
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(
+            "DESCRIPTION:Gross Domestic Product\\, Advance\\nPress release: yes\\nLink: https://www.bea.gov/\r\n"
+        ));
+        assert!(unfolded.contains("DESCRIPTION:Consumer Price Index\r\n"));
+    }
+}
@@ -0,0 +1,425 @@
+//! A local catalog of FRED metadata, persisted as a directory of JSON files. Once populated, the
+//! catalog answers lookups without network access, and `refresh()` brings tracked series up to
+//! date using `series/updates`.
+//!
+//! ```
+//! let mut crawler = CategoryCrawler::new(CategoryId::new(32991));
+//! crawler.run()?;
+//! let mut catalog = Catalog::from_crawl(crawler.state());
+//! catalog.fetch_releases_and_sources()?;
+//! catalog.save("catalog")?;
+//!
+//! let mut catalog = Catalog::load("catalog")?;
+//! let report = catalog.refresh()?;
+//! println!("{} series updated", report.updated.len());
+//! catalog.save("catalog")?;
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+use crate::{
+    req,
+    Category,
+    CategoryId,
+    CrawlState,
+    FredClient,
+    FredRequest,
+    ReleaseId,
+    ReleaseItem,
+    SeriesId,
+    SeriesItem,
+    SeriesItems,
+    SeriesUpdates,
+    SourceId,
+    SourceItem,
+    Tag,
+    TagName,
+    Tags,
+};
+
+// The maximum `limit` of `series/updates`.
+const UPDATES_PAGE_LIMIT: usize = 1000;
+
+// The maximum `limit` of `tags`.
+const TAGS_PAGE_LIMIT: usize = 1000;
+
+/// Categories, releases, sources, tags and series metadata held locally.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Catalog {
+    pub categories:                 BTreeMap<CategoryId, Category>,
+    pub category_series:            BTreeMap<CategoryId, BTreeSet<SeriesId>>,
+    pub series:                     BTreeMap<SeriesId, SeriesItem>,
+    pub series_tags:                BTreeMap<SeriesId, Vec<TagName>>,
+    pub releases:                   BTreeMap<ReleaseId, ReleaseItem>,
+    pub sources:                    BTreeMap<SourceId, SourceItem>,
+    pub source_releases:            BTreeMap<SourceId, BTreeSet<ReleaseId>>,
+    pub tags:                       BTreeMap<TagName, Tag>,
+    /// The latest `last_updated` seen by `refresh()`. Older updates are not fetched again.
+    pub updated_through:            Option<String>,
+}
+
+/// The outcome of `Catalog::refresh()`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CatalogRefresh {
+    /// Tracked series whose metadata changed.
+    pub updated:                    Vec<SeriesId>,
+    /// The number of updates read from `series/updates`.
+    pub checked:                    usize,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// A catalog of the categories and series collected by a crawl.
+    pub fn from_crawl(state: &CrawlState) -> Self {
+        let mut catalog = Catalog::new();
+        for (id, crawled) in &state.crawled {
+            catalog.categories.insert(*id, crawled.category.clone());
+            for series in &crawled.series {
+                catalog.insert_series(*id, series.clone());
+            }
+        }
+        catalog
+    }
+
+    /// Add a series to the catalog as a member of `category_id`.
+    pub fn insert_series(&mut self, category_id: CategoryId, series: SeriesItem) {
+        self.category_series.entry(category_id).or_default().insert(series.id.clone());
+        self.series.insert(series.id.clone(), series);
+    }
+
+    // Lookups ////////////////////////////////////////////////////////////////////////////////////
+
+    pub fn series_item(&self, series_id: &SeriesId) -> Option<&SeriesItem> {
+        self.series.get(series_id)
+    }
+
+    pub fn category(&self, category_id: CategoryId) -> Option<&Category> {
+        self.categories.get(&category_id)
+    }
+
+    /// The child categories of `category_id`.
+    pub fn category_children(&self, category_id: CategoryId) -> Vec<&Category> {
+        self.categories
+            .values()
+            .filter(|category| category.parent_id == category_id && category.id != category_id)
+            .collect()
+    }
+
+    /// The series in `category_id`.
+    pub fn category_series(&self, category_id: CategoryId) -> SeriesItems {
+        SeriesItems(
+            self.category_series
+                .get(&category_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| self.series.get(id).cloned())
+                .collect()
+        )
+    }
+
+    /// All series in the catalog.
+    pub fn all_series(&self) -> SeriesItems {
+        SeriesItems(self.series.values().cloned().collect())
+    }
+
+    /// The tags of a series, empty if they have not been fetched.
+    pub fn tags_of(&self, series_id: &SeriesId) -> &[TagName] {
+        self.series_tags.get(series_id).map(|tags| tags.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn release(&self, release_id: ReleaseId) -> Option<&ReleaseItem> {
+        self.releases.get(&release_id)
+    }
+
+    pub fn source(&self, source_id: SourceId) -> Option<&SourceItem> {
+        self.sources.get(&source_id)
+    }
+
+    /// The releases of `source_id`.
+    pub fn source_releases(&self, source_id: SourceId) -> Vec<&ReleaseItem> {
+        self.source_releases
+            .get(&source_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.releases.get(id))
+            .collect()
+    }
+
+    pub fn tag(&self, name: &TagName) -> Option<&Tag> {
+        self.tags.get(name)
+    }
+
+    // Fetching ///////////////////////////////////////////////////////////////////////////////////
+
+    /// Fetch all releases and sources, and the releases of each source.
+    pub fn fetch_releases_and_sources(&mut self) -> Result<()> {
+        for release in FredClient::releases()?.releases {
+            self.releases.insert(release.id, release);
+        }
+        let sources = FredClient::sources()?.sources;
+        for source in sources {
+            let releases = FredClient::source_releases(source.id)?.releases;
+            self.source_releases.insert(source.id, releases.iter().map(|r| r.id).collect());
+            for release in releases {
+                self.releases.entry(release.id).or_insert(release);
+            }
+            self.sources.insert(source.id, source);
+        }
+        Ok(())
+    }
+
+    /// Fetch all tags, a page at a time.
+    pub fn fetch_tags(&mut self) -> Result<()> {
+        self.fetch_tags_with(|offset| {
+            req(FredRequest::new("tags", vec![("limit", TAGS_PAGE_LIMIT), ("offset", offset)])?)
+        })
+    }
+
+    fn fetch_tags_with<F>(&mut self, mut fetch: F) -> Result<()>
+    where
+        F: FnMut(usize) -> Result<Tags>,
+    {
+        let mut offset = 0;
+        loop {
+            let page = fetch(offset)?;
+            let fetched = page.tags.len();
+            offset += fetched;
+            for tag in page.tags {
+                self.tags.insert(tag.name.clone(), tag);
+            }
+            if fetched == 0 || offset as isize >= page.count {
+                return Ok(())
+            }
+        }
+    }
+
+    /// Fetch the tags of each series in `series_ids`.
+    pub fn fetch_series_tags(&mut self, series_ids: &[SeriesId]) -> Result<()> {
+        for id in series_ids {
+            let tags = FredClient::series_tags(id)?.tags;
+            self.series_tags.insert(id.clone(), tags.into_iter().map(|tag| tag.name).collect());
+        }
+        Ok(())
+    }
+
+    /// Update the metadata of tracked series from `series/updates`, newest first, stopping at
+    /// updates already seen. Series that are not in the catalog are ignored. FRED only reports
+    /// updates from the last two weeks, so a catalog refreshed less often should be rebuilt.
+    pub fn refresh(&mut self) -> Result<CatalogRefresh> {
+        self.refresh_with(|offset| {
+            req(FredRequest::new(
+                "series/updates",
+                vec![("limit", UPDATES_PAGE_LIMIT), ("offset", offset)],
+            )?)
+        })
+    }
+
+    fn refresh_with<F>(&mut self, mut fetch: F) -> Result<CatalogRefresh>
+    where
+        F: FnMut(usize) -> Result<SeriesUpdates>,
+    {
+        let mut report = CatalogRefresh::default();
+        let mut latest = self.updated_through.clone();
+        let mut offset = 0;
+
+        'pages: loop {
+            let page = fetch(offset)?;
+            let fetched = page.seriess.0.len();
+            for item in page.seriess.0 {
+                if let Some(through) = &self.updated_through {
+                    if cmp_updated(&item.last_updated, through) != Ordering::Greater {
+                        break 'pages
+                    }
+                }
+                report.checked += 1;
+                if latest.as_ref().is_none_or(|l| cmp_updated(&item.last_updated, l) == Ordering::Greater) {
+                    latest = Some(item.last_updated.clone());
+                }
+                if let Some(existing) = self.series.get_mut(&item.id) {
+                    if *existing != item {
+                        report.updated.push(item.id.clone());
+                        *existing = item;
+                    }
+                }
+            }
+            offset += fetched;
+            if fetched == 0 || offset as isize >= page.count {
+                break
+            }
+        }
+        self.updated_through = latest;
+        Ok(report)
+    }
+
+    // Persistence ////////////////////////////////////////////////////////////////////////////////
+
+    /// Read a catalog written by `save()`. Missing files are left empty.
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Catalog> {
+        let dir = dir.as_ref();
+        Ok(
+            Catalog {
+                categories:         load_file(dir, "categories")?,
+                category_series:    load_file(dir, "category_series")?,
+                series:             load_file(dir, "series")?,
+                series_tags:        load_file(dir, "series_tags")?,
+                releases:           load_file(dir, "releases")?,
+                sources:            load_file(dir, "sources")?,
+                source_releases:    load_file(dir, "source_releases")?,
+                tags:               load_file(dir, "tags")?,
+                updated_through:    load_file(dir, "updated_through")?,
+            }
+        )
+    }
+
+    /// Write the catalog to `dir`, one JSON file per table.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).context(format!("Failed to create [{}]", dir.display()))?;
+        save_file(dir, "categories", &self.categories)?;
+        save_file(dir, "category_series", &self.category_series)?;
+        save_file(dir, "series", &self.series)?;
+        save_file(dir, "series_tags", &self.series_tags)?;
+        save_file(dir, "releases", &self.releases)?;
+        save_file(dir, "sources", &self.sources)?;
+        save_file(dir, "source_releases", &self.source_releases)?;
+        save_file(dir, "tags", &self.tags)?;
+        save_file(dir, "updated_through", &self.updated_through)
+    }
+}
+
+fn load_file<T: DeserializeOwned + Default>(dir: &Path, name: &str) -> Result<T> {
+    let path = dir.join(format!("{}.json", name));
+    if !path.exists() {
+        return Ok(T::default())
+    }
+    let s = fs::read_to_string(&path).context(format!("Failed to read [{}]", path.display()))?;
+    serde_json::from_str(&s).context(format!("Failed to parse [{}]", path.display()))
+}
+
+fn save_file<T: Serialize>(dir: &Path, name: &str, value: &T) -> Result<()> {
+    let path = dir.join(format!("{}.json", name));
+    fs::write(&path, serde_json::to_string(value)?)
+        .context(format!("Failed to write [{}]", path.display()))
+}
+
+// Compare `last_updated` timestamps such as `2013-07-31 09:26:16-05`, which carry their own
+// offset. Falls back to comparing strings if either fails to parse.
+pub(crate) fn cmp_updated(a: &str, b: &str) -> Ordering {
+    let parse = |s: &str| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%#z");
+    match (parse(a), parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+    use crate::{
+        catalog::cmp_updated,
+        Catalog,
+        CategoryId,
+        SeriesId,
+        SeriesItem,
+        SeriesUpdates,
+        TagName,
+        Tags,
+    };
+
+    fn series_item(id: &str, last_updated: &str) -> SeriesItem {
+        SeriesItem { last_updated: last_updated.into(), ..crate::test::series_item(id) }
+    }
+
+    fn updates(items: Vec<SeriesItem>) -> SeriesUpdates {
+        serde_json::from_value(serde_json::json!({
+            "realtime_start": "2020-01-01",
+            "realtime_end": "2020-01-01",
+            "filter_variable": "geography",
+            "filter_value": "all",
+            "order_by": "last_updated",
+            "sort_order": "desc",
+            "count": items.len(),
+            "offset": 0,
+            "limit": 1000,
+            "seriess": items,
+        })).unwrap()
+    }
+
+    #[test]
+    fn refresh_updates_tracked_series_once() {
+        let mut catalog = Catalog::new();
+        catalog.insert_series(CategoryId::new(1), series_item("AAA", "2020-01-01 08:00:00-05"));
+        catalog.insert_series(CategoryId::new(1), series_item("BBB", "2020-01-01 08:00:00-05"));
+
+        let page = updates(vec![
+            series_item("CCC", "2020-02-02 08:00:00-05"),
+            series_item("AAA", "2020-02-01 08:00:00-05"),
+        ]);
+        let report = catalog.refresh_with(|_| Ok(page.clone())).unwrap();
+        assert_eq!(report.updated, vec![SeriesId::new("AAA").unwrap()]);
+        assert_eq!(report.checked, 2);
+        assert_eq!(catalog.updated_through.as_deref(), Some("2020-02-02 08:00:00-05"));
+        assert!(catalog.series_item(&SeriesId::new("CCC").unwrap()).is_none());
+
+        let report = catalog.refresh_with(|_| Ok(page.clone())).unwrap();
+        assert!(report.updated.is_empty());
+        assert_eq!(report.checked, 0);
+    }
+
+    #[test]
+    fn fetch_tags_reads_every_page() {
+        let names = ["gdp", "usa", "nation"];
+        let page = |offset: usize| -> Tags {
+            let tags: Vec<_> = names[offset..].iter().take(2).map(|name| serde_json::json!({
+                "name": name,
+                "group_id": "gen",
+                "notes": null,
+                "created": "2012-02-27 10:18:19-06",
+                "popularity": 50,
+                "series_count": 10,
+            })).collect();
+            serde_json::from_value(serde_json::json!({
+                "realtime_start": "2020-01-01",
+                "realtime_end": "2020-01-01",
+                "order_by": "series_count",
+                "sort_order": "desc",
+                "count": names.len(),
+                "offset": offset,
+                "limit": 2,
+                "tags": tags,
+            })).unwrap()
+        };
+
+        let mut catalog = Catalog::new();
+        let mut offsets = Vec::new();
+        catalog.fetch_tags_with(|offset| { offsets.push(offset); Ok(page(offset)) }).unwrap();
+        assert_eq!(offsets, vec![0, 2]);
+        assert_eq!(catalog.tags.len(), 3);
+        assert!(catalog.tag(&TagName::new("nation").unwrap()).is_some());
+    }
+
+    #[test]
+    fn catalog_saves_and_loads() {
+        let mut catalog = Catalog::new();
+        catalog.insert_series(CategoryId::new(7), series_item("AAA", "2020-01-01 08:00:00-05"));
+
+        let dir = std::env::temp_dir().join(format!("fred_api_catalog_{}", std::process::id()));
+        catalog.save(&dir).unwrap();
+        let loaded = Catalog::load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded, catalog);
+        assert_eq!(loaded.category_series(CategoryId::new(7)).inner().len(), 1);
+        assert_eq!(cmp_updated("2020-01-01 08:00:00-05", "2020-01-01 08:00:00-06"), Ordering::Less);
+    }
+}
@@ -0,0 +1,442 @@
+//! A crawler that walks a category subtree, collecting each category with its children and
+//! series. With a state path the crawl is saved as it goes, so running the same code again after
+//! an interruption continues where it stopped.
+//!
+//! ```
+//! let mut crawler = CategoryCrawler::new(CategoryId::new(0))
+//!     .max_depth(2)
+//!     .concurrency(4)
+//!     .state_path("crawl")?;
+//! crawler.run()?;
+//! let tree = crawler.state().tree();
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+use crate::{
+    req,
+    Category,
+    CategoryId,
+    CategorySeries,
+    FredClient,
+    FredRequest,
+    SeriesItem,
+};
+
+// The maximum `limit` of `category/series`.
+const SERIES_PAGE_LIMIT: usize = 1000;
+
+// A saved crawl is a directory holding each crawled category as a line of JSON, appended as the
+// category is crawled, and the root with the pending categories, rewritten after every batch.
+const CRAWLED_FILE: &str = "crawled.jsonl";
+const PENDING_FILE: &str = "pending.json";
+
+/// Spaces out requests shared between threads so that no more than `requests_per_minute` are
+/// made. FRED allows 120 requests per minute per API key.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval:   Duration,
+    next:       Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        RateLimiter {
+            interval:   Duration::from_secs(60) / requests_per_minute.max(1),
+            next:       Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Block until the next request is allowed.
+    pub fn wait(&self) {
+        let wait = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + self.interval;
+            slot - now
+        };
+        if wait > Duration::from_secs(0) {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// A category collected by the crawler.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CrawledCategory {
+    pub category:                   Category,
+    pub depth:                      usize,
+    pub children:                   Vec<CategoryId>,
+    pub series:                     Vec<SeriesItem>,
+}
+
+/// A category with its series and child categories.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CategoryNode {
+    pub category:                   Category,
+    pub series:                     Vec<SeriesItem>,
+    pub children:                   Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+    /// The number of categories in this subtree, including this one.
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(|child| child.count()).sum::<usize>()
+    }
+
+    /// The categories of this subtree, depth first.
+    pub fn walk(&self) -> Vec<&CategoryNode> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.walk());
+        }
+        nodes
+    }
+}
+
+/// The progress of a crawl. Categories are moved from `pending` to `crawled` as they are fetched.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CrawlState {
+    pub root:                       CategoryId,
+    pub crawled:                    BTreeMap<CategoryId, CrawledCategory>,
+    pub pending:                    VecDeque<(CategoryId, usize)>,
+}
+
+// The contents of `PENDING_FILE`.
+#[derive(Deserialize, Serialize)]
+struct PendingCategories {
+    root:                           CategoryId,
+    pending:                        VecDeque<(CategoryId, usize)>,
+}
+
+impl CrawlState {
+    fn new(root: CategoryId) -> Self {
+        let mut pending = VecDeque::new();
+        pending.push_back((root, 0));
+        CrawlState { root, crawled: BTreeMap::new(), pending }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Load a crawl saved in directory `dir`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<CrawlState> {
+        let dir = dir.as_ref();
+        let path = dir.join(PENDING_FILE);
+        let s = fs::read_to_string(&path).context(format!("Failed to read [{}]", path.display()))?;
+        let PendingCategories { root, pending } = serde_json::from_str(&s)
+            .context(format!("Failed to parse [{}]", path.display()))?;
+
+        let path = dir.join(CRAWLED_FILE);
+        let s = if path.exists() {
+            fs::read_to_string(&path).context(format!("Failed to read [{}]", path.display()))?
+        } else {
+            String::new()
+        };
+        let mut crawled = BTreeMap::new();
+        // A line cut short by an interruption is dropped, and its category crawled again.
+        for line in s.split_inclusive('\n').filter(|line| line.ends_with('\n')) {
+            let category: CrawledCategory = serde_json::from_str(line)
+                .context(format!("Failed to parse [{}]", path.display()))?;
+            crawled.insert(category.category.id, category);
+        }
+        Ok(CrawlState { root, crawled, pending })
+    }
+
+    /// Save to directory `dir`, rewriting any crawl saved there.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).context(format!("Failed to create [{}]", dir.display()))?;
+        let mut lines = String::new();
+        for crawled in self.crawled.values() {
+            lines.push_str(&serde_json::to_string(crawled)?);
+            lines.push('\n');
+        }
+        write_through_tmp(&dir.join(CRAWLED_FILE), &lines)?;
+        self.save_pending(dir)
+    }
+
+    // Append `crawled` to the crawl saved in `dir`.
+    fn append(dir: &Path, crawled: &[&CrawledCategory]) -> Result<()> {
+        let path = dir.join(CRAWLED_FILE);
+        let mut lines = String::new();
+        for crawled in crawled {
+            lines.push_str(&serde_json::to_string(crawled)?);
+            lines.push('\n');
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .context(format!("Failed to write [{}]", path.display()))
+    }
+
+    fn save_pending(&self, dir: &Path) -> Result<()> {
+        let pending = PendingCategories { root: self.root, pending: self.pending.clone() };
+        write_through_tmp(&dir.join(PENDING_FILE), &serde_json::to_string(&pending)?)
+    }
+
+    /// The tree of categories crawled so far, or `None` if the root has not been crawled.
+    /// Children that have not been crawled are left out.
+    pub fn tree(&self) -> Option<CategoryNode> {
+        self.node(self.root)
+    }
+
+    fn node(&self, id: CategoryId) -> Option<CategoryNode> {
+        let crawled = self.crawled.get(&id)?;
+        Some(
+            CategoryNode {
+                category:   crawled.category.clone(),
+                series:     crawled.series.clone(),
+                children:   crawled.children.iter().filter_map(|child| self.node(*child)).collect(),
+            }
+        )
+    }
+}
+
+// Write `path` through a temporary file, so that an interrupted write leaves the previous contents
+// intact.
+fn write_through_tmp(path: &Path, contents: &str) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents).context(format!("Failed to write [{}]", tmp.display()))?;
+    fs::rename(&tmp, path).context(format!("Failed to write [{}]", path.display()))
+}
+
+type FetchCategory = Box<dyn Fn(CategoryId) -> Result<(Category, Vec<CategoryId>, Vec<SeriesItem>)> + Sync>;
+
+/// Walks a category subtree breadth first, fetching up to `concurrency` categories at a time.
+pub struct CategoryCrawler {
+    state:          CrawlState,
+    state_path:     Option<PathBuf>,
+    max_depth:      Option<usize>,
+    concurrency:    usize,
+    fetch:          FetchCategory,
+}
+
+impl CategoryCrawler {
+    /// Crawl the subtree under `root`. Use `CategoryId::new(0)` for all of FRED.
+    pub fn new(root: CategoryId) -> Self {
+        CategoryCrawler::resume(CrawlState::new(root))
+    }
+
+    /// Continue a crawl from a saved state.
+    pub fn resume(state: CrawlState) -> Self {
+        CategoryCrawler {
+            state,
+            state_path:     None,
+            max_depth:      None,
+            concurrency:    4,
+            fetch:          Box::new(fetch_category),
+        }
+    }
+
+    /// Save the crawl in directory `path` as it goes, and continue the crawl saved there if there
+    /// is one. Fails if the saved crawl is of a different root.
+    pub fn state_path<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if path.join(PENDING_FILE).exists() {
+            let state = CrawlState::load(path)?;
+            if state.root != self.state.root {
+                return Err(anyhow!(
+                    "Crawl state in [{}] is for category [{}], not [{}].",
+                    path.display(),
+                    state.root,
+                    self.state.root,
+                ))
+            }
+            self.state = state;
+        } else {
+            self.state.save(path)?;
+        }
+        self.state_path = Some(path.to_path_buf());
+        Ok(self)
+    }
+
+    /// Don't crawl the children of categories at `max_depth`. The root is at depth 0.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The number of categories fetched at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn state(&self) -> &CrawlState {
+        &self.state
+    }
+
+    pub fn into_state(self) -> CrawlState {
+        self.state
+    }
+
+    /// Crawl until there are no pending categories. On error, categories that failed remain
+    /// pending so the crawl can be resumed. With a state path, the categories of each batch are
+    /// appended to the saved crawl and the pending categories rewritten.
+    pub fn run(&mut self) -> Result<()> {
+        while !self.state.pending.is_empty() {
+            let n = self.concurrency.min(self.state.pending.len());
+            let batch: Vec<(CategoryId, usize)> = self.state.pending.drain(..n).collect();
+
+            let fetch = &self.fetch;
+            let results: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|(id, _)| scope.spawn(move || fetch(*id)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap_or_else(|_| Err(anyhow!("Crawler thread panicked."))))
+                    .collect()
+            });
+
+            let mut first_err = None;
+            let mut finished = Vec::new();
+            for ((id, depth), result) in batch.into_iter().zip(results) {
+                match result {
+                    Ok((category, children, series)) => {
+                        if self.max_depth.is_none_or(|max| depth < max) {
+                            for child in &children {
+                                if !self.state.crawled.contains_key(child) {
+                                    self.state.pending.push_back((*child, depth + 1));
+                                }
+                            }
+                        }
+                        self.state.crawled.insert(id, CrawledCategory { category, depth, children, series });
+                        finished.push(id);
+                    },
+                    Err(err) => {
+                        self.state.pending.push_back((id, depth));
+                        if first_err.is_none() {
+                            first_err = Some(err.context(format!("Failed to crawl category [{}]", id)));
+                        }
+                    },
+                }
+            }
+            if let Some(dir) = &self.state_path {
+                let finished: Vec<_> = finished.iter().map(|id| &self.state.crawled[id]).collect();
+                CrawlState::append(dir, &finished)?;
+                self.state.save_pending(dir)?;
+            }
+            if let Some(err) = first_err {
+                return Err(err)
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fetch_category(id: CategoryId) -> Result<(Category, Vec<CategoryId>, Vec<SeriesItem>)> {
+    let category = FredClient::category(id)?
+        .categories
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Category [{}] was not found.", id))?;
+
+    let children = FredClient::category_children(id)?
+        .categories
+        .into_iter()
+        .map(|child| child.id)
+        .collect();
+
+    let mut series = Vec::new();
+    loop {
+        let page: CategorySeries = req(FredRequest::new(
+            "category/series",
+            vec![
+                ("category_id", id.to_string()),
+                ("limit", SERIES_PAGE_LIMIT.to_string()),
+                ("offset", series.len().to_string()),
+            ],
+        )?)?;
+        let fetched = page.seriess.0.len();
+        series.extend(page.seriess.0);
+        if fetched == 0 || series.len() as isize >= page.count {
+            break
+        }
+    }
+    Ok((category, children, series))
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::anyhow;
+    use crate::{Category, CategoryCrawler, CategoryId, CrawlState};
+
+    fn fake_crawler(root: usize, fail: Option<usize>) -> CategoryCrawler {
+        let mut crawler = CategoryCrawler::new(CategoryId::new(root));
+        // Category n has children 10n + 1 and 10n + 2, down to three levels.
+        crawler.fetch = Box::new(move |id| {
+            let n = id.value();
+            if Some(n) == fail {
+                return Err(anyhow!("network error"))
+            }
+            let category = Category {
+                id,
+                name:       format!("category {}", n),
+                parent_id:  CategoryId::new(n / 10),
+                notes:      None,
+            };
+            let children = if n < 100 {
+                vec![CategoryId::new(10 * n + 1), CategoryId::new(10 * n + 2)]
+            } else {
+                Vec::new()
+            };
+            Ok((category, children, Vec::new()))
+        });
+        crawler
+    }
+
+    #[test]
+    fn crawl_respects_depth_limit() {
+        let mut crawler = fake_crawler(1, None).max_depth(1);
+        crawler.run().unwrap();
+        let tree = crawler.state().tree().unwrap();
+        assert_eq!(tree.count(), 3);
+        assert!(tree.children.iter().all(|child| child.children.is_empty()));
+    }
+
+    #[test]
+    fn crawl_resumes_after_failure() {
+        let path = std::env::temp_dir().join(format!("fred_api_crawl_{}", std::process::id()));
+        let mut crawler = fake_crawler(1, Some(12)).state_path(&path).unwrap();
+        assert!(crawler.run().is_err());
+        let state = CrawlState::load(&path).unwrap();
+        assert!(state.pending.iter().any(|(id, _)| id.value() == 12));
+        assert_eq!(&state, crawler.state());
+
+        // A new process picks up the saved crawl.
+        let mut resumed = fake_crawler(1, None).state_path(&path).unwrap();
+        assert_eq!(resumed.state(), &state);
+        resumed.run().unwrap();
+        assert!(CrawlState::load(&path).unwrap().is_complete());
+        assert_eq!(resumed.state().tree().unwrap().count(), 7);
+
+        // Each category was appended once, and a line cut short is dropped.
+        let crawled = path.join("crawled.jsonl");
+        assert_eq!(std::fs::read_to_string(&crawled).unwrap().lines().count(), 7);
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&crawled)
+            .and_then(|mut file| std::io::Write::write_all(&mut file, b"{\"category\":"))
+            .unwrap();
+        assert_eq!(&CrawlState::load(&path).unwrap(), resumed.state());
+
+        assert!(fake_crawler(2, None).state_path(&path).is_err());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
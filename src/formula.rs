@@ -0,0 +1,682 @@
+//! Series derived from a formula over series ids, like the formula feature of FRED Graph. A
+//! formula has numbers, series ids, `+ - * /`, parentheses and the functions
+//!
+//! | function          | value                                   |
+//! |-------------------|-----------------------------------------|
+//! | `log(x)`          | natural log                             |
+//! | `lag(x, n)`       | the value `n` periods earlier           |
+//! | `diff(x, n)`      | change from `n` periods earlier         |
+//! | `pct(x, n)`       | percent change from `n` periods earlier |
+//! | `min(x, y, ...)`  | the smallest of the values              |
+//! | `max(x, y, ...)`  | the largest of the values               |
+//!
+//! `n` is an integer and defaults to one for `diff` and `pct`. Series ids are not case sensitive.
+//! The operands of each operator and function are joined on the dates they share, and a value is
+//! missing where any value it depends on is missing. `lag`, `diff` and `pct` count periods on the
+//! dates of their argument, so `pct(GDP)` is the growth from the previous quarter even where the
+//! series it is joined with has no value for that quarter.
+//!
+//! ```
+//! let formula = Formula::parse("(GDP / GDPDEF) * 100")?;
+//! let real_gdp = FredClient::formula(&formula)?;
+//! let spread = FredClient::formula(&Formula::parse("GS10 - FEDFUNDS")?.frequency(Frequency::Monthly, Aggregation::Avg))?;
+//! ```
+
+use anyhow::{anyhow, Result};
+use std::{fmt, str::FromStr};
+use crate::{
+    catalog::cmp_updated,
+    panel::at_frequency,
+    Aggregation,
+    FredClient,
+    Frequency,
+    Join,
+    Panel,
+    SeriesId,
+    SeriesItem,
+    TimeSeries,
+};
+
+/// A parsed formula.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Formula {
+    expr:                           Expr,
+    frequency:                      Option<(Frequency, Aggregation)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Number(f64),
+    Series(SeriesId),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Log(Box<Expr>),
+    Lag(Box<Expr>, usize),
+    Diff(Box<Expr>, usize),
+    Pct(Box<Expr>, usize),
+    Min(Vec<Expr>),
+    Max(Vec<Expr>),
+}
+
+impl Formula {
+    /// Parse a formula. Fails on a syntax error or if the formula refers to no series.
+    pub fn parse(formula: &str) -> Result<Formula> {
+        let mut parser = Parser { tokens: tokenize(formula)?, pos: 0 };
+        let expr = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            return Err(anyhow!("Unexpected [{}] in formula [{}].", token, formula))
+        }
+        let formula = Formula { expr, frequency: None };
+        if formula.series_ids().is_empty() {
+            return Err(anyhow!("Formula [{}] refers to no series.", formula))
+        }
+        Ok(formula)
+    }
+
+    /// Resample every series to `frequency` with `method` before joining them.
+    pub fn frequency(mut self, frequency: Frequency, method: Aggregation) -> Self {
+        self.frequency = Some((frequency, method));
+        self
+    }
+
+    /// The series the formula refers to, in order of first appearance.
+    pub fn series_ids(&self) -> Vec<SeriesId> {
+        let mut ids = Vec::new();
+        self.expr.visit(&mut |id| {
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        });
+        ids
+    }
+
+    /// Evaluate the formula over `series`, which must include every series the formula refers
+    /// to, with metadata. Unless a frequency is set, series of different frequencies are averaged
+    /// to the lowest of them, as in FRED Graph. The result has metadata with the formula as title,
+    /// generated units, and the titles of the series in the notes.
+    pub fn evaluate(&self, series: &[TimeSeries]) -> Result<TimeSeries> {
+        let ids = self.series_ids();
+        let given = ids
+            .iter()
+            .map(|id| {
+                series
+                    .iter()
+                    .find(|ts| ts.id().is_some_and(|ts_id| ts_id.as_str().eq_ignore_ascii_case(id.as_str())))
+                    .ok_or_else(|| anyhow!("Series [{}] is in the formula but was not given.", id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let frequency = self.frequency.clone().or_else(|| {
+            let lowest = lowest_frequency(&given)?;
+            let mixed = given.iter().any(|ts| ts.frequency().and_then(|f| f.per_year()) != lowest.per_year());
+            mixed.then_some((lowest, Aggregation::Avg))
+        });
+        let inputs = ids
+            .iter()
+            .zip(&given)
+            .map(|(id, ts)| {
+                let ts = match &frequency {
+                    Some((frequency, method)) => at_frequency(ts, frequency, *method)?,
+                    None => (*ts).clone(),
+                };
+                Ok((id.clone(), ts))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut result = match self.expr.evaluate(&inputs)? {
+            Value::Series(ts) => *ts,
+            Value::Number(_) => return Err(anyhow!("Formula [{}] refers to no series.", self)),
+        };
+        let metas = inputs
+            .iter()
+            .map(|(_, ts)| ts.meta.as_ref().ok_or_else(|| anyhow!("A series in the formula has no metadata.")))
+            .collect::<Result<Vec<_>>>()?;
+        let frequency = lowest_frequency(&inputs.iter().map(|(_, ts)| ts).collect::<Vec<_>>());
+        result.meta = Some(self.meta(&ids, &metas, frequency, &result)?);
+        Ok(result)
+    }
+
+    // Metadata for the result, given the metadata of each series in `ids` and the frequency of
+    // the result, if known.
+    fn meta(
+        &self,
+        ids: &[SeriesId],
+        inputs: &[&SeriesItem],
+        frequency: Option<Frequency>,
+        result: &TimeSeries,
+    ) -> Result<SeriesItem> {
+        let first = inputs[0];
+        let find = |id: &SeriesId| ids.iter().position(|i| i == id).map(|k| inputs[k]).unwrap_or(first);
+        let all_same = |field: fn(&SeriesItem) -> &String| {
+            inputs.iter().all(|meta| field(meta) == field(first)).then(|| field(first).clone())
+        };
+        let formula = self.to_string();
+        // Prefixed so that it cannot be the id of a FRED series, as `-GDP` would otherwise be `GDP`.
+        let id = format!(
+            "FORMULA_{}",
+            formula
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("_"),
+        );
+        let notes = inputs
+            .iter()
+            .map(|meta| format!("{}: {}", meta.id, meta.title))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(SeriesItem {
+            id:                         SeriesId::new(&id)?,
+            realtime_start:             first.realtime_start.clone(),
+            realtime_end:               first.realtime_end.clone(),
+            title:                      formula.clone(),
+            observation_start:          result.first_valid().map(|(date, _)| date.to_string()).unwrap_or_default(),
+            observation_end:            result.last_valid().map(|(date, _)| date.to_string()).unwrap_or_default(),
+            frequency:                  frequency.as_ref().map(|f| f.to_string()).unwrap_or_else(|| first.frequency.clone()),
+            frequency_short:            frequency.as_ref().map(|f| f.short()).or_else(|| first.frequency_short.clone()),
+            units:                      self.expr.units(&|id| find(id).units.clone()).unwrap_or_else(|| "Ratio".into()),
+            units_short:                self.expr.units(&|id| find(id).units_short.clone()).unwrap_or_else(|| "Ratio".into()),
+            seasonal_adjustment:        all_same(|meta| &meta.seasonal_adjustment).unwrap_or_else(|| "Mixed".into()),
+            seasonal_adjustment_short:  all_same(|meta| &meta.seasonal_adjustment_short).unwrap_or_default(),
+            last_updated:               inputs
+                .iter()
+                .map(|meta| &meta.last_updated)
+                .max_by(|a, b| cmp_updated(a, b))
+                .cloned()
+                .unwrap_or_default(),
+            popularity:                 0,
+            group_popularity:           None,
+            notes:                      Some(format!("Formula: {}\n{}", formula, notes)),
+        })
+    }
+}
+
+impl Expr {
+    fn visit<F: FnMut(&SeriesId)>(&self, f: &mut F) {
+        match self {
+            Expr::Number(_) => (),
+            Expr::Series(id) => f(id),
+            Expr::Neg(x) | Expr::Log(x) | Expr::Lag(x, _) | Expr::Diff(x, _) | Expr::Pct(x, _) => x.visit(f),
+            Expr::Binary(_, a, b) => {
+                a.visit(f);
+                b.visit(f);
+            },
+            Expr::Min(xs) | Expr::Max(xs) => xs.iter().for_each(|x| x.visit(f)),
+        }
+    }
+
+    fn evaluate(&self, inputs: &[(SeriesId, TimeSeries)]) -> Result<Value> {
+        let lagged = |x: &Expr, lag: usize, f: fn(f64, f64) -> f64| -> Result<Value> {
+            Ok(match x.evaluate(inputs)? {
+                Value::Number(c) => Value::Number(f(c, c)),
+                Value::Series(ts) => {
+                    let values = ts.values();
+                    let lagged = (0..values.len()).map(|i| Some(f(values[i]?, values[i.checked_sub(lag)?]?)));
+                    Value::Series(Box::new(TimeSeries::from_parts(ts.dates().to_vec(), finite(lagged), None)))
+                },
+            })
+        };
+        let operands = |xs: &[&Expr]| xs.iter().map(|x| x.evaluate(inputs)).collect::<Result<Vec<_>>>();
+        match self {
+            Expr::Number(c) => Ok(Value::Number(*c)),
+            Expr::Series(id) => inputs
+                .iter()
+                .find(|(input, _)| input == id)
+                .map(|(_, ts)| Value::Series(Box::new(ts.clone())))
+                .ok_or_else(|| anyhow!("Series [{}] is in the formula but was not given.", id)),
+            Expr::Neg(x) => combine(operands(&[x])?, |v| -v[0]),
+            Expr::Binary(op, a, b) => combine(operands(&[a, b])?, |v| match op {
+                '+' => v[0] + v[1],
+                '-' => v[0] - v[1],
+                '*' => v[0] * v[1],
+                _ => v[0] / v[1],
+            }),
+            Expr::Log(x) => combine(operands(&[x])?, |v| v[0].ln()),
+            Expr::Lag(x, lag) => lagged(x, *lag, |_, before| before),
+            Expr::Diff(x, lag) => lagged(x, *lag, |now, before| now - before),
+            Expr::Pct(x, lag) => lagged(x, *lag, |now, before| (now / before - 1.0) * 100.0),
+            Expr::Min(xs) | Expr::Max(xs) => {
+                let pick = if let Expr::Min(_) = self { f64::min } else { f64::max };
+                combine(operands(&xs.iter().collect::<Vec<_>>())?, |v| v.iter().copied().reduce(pick).unwrap_or(f64::NAN))
+            },
+        }
+    }
+
+    // The units of the value, or `None` if it has no units.
+    fn units(&self, lookup: &dyn Fn(&SeriesId) -> String) -> Option<String> {
+        match self {
+            Expr::Number(_) => None,
+            Expr::Series(id) => Some(lookup(id)),
+            Expr::Neg(x) | Expr::Lag(x, _) | Expr::Diff(x, _) => x.units(lookup),
+            Expr::Log(x) => x.units(lookup).map(|units| format!("Natural Log of {}", units)),
+            Expr::Pct(_, _) => Some("Percent Change".into()),
+            Expr::Binary(op, a, b) => match (op, a.units(lookup), b.units(lookup)) {
+                ('+', a, b) | ('-', a, b) => match (a, b) {
+                    (Some(a), Some(b)) if a != b => Some("Mixed Units".into()),
+                    (a, b) => a.or(b),
+                },
+                ('*', Some(a), Some(b)) => Some(format!("{} times {}", a, b)),
+                ('*', a, b) => a.or(b),
+                (_, Some(a), Some(b)) if a == b => None,
+                (_, Some(a), Some(b)) => Some(format!("{} per {}", a, b)),
+                (_, Some(a), None) => Some(a),
+                (_, None, Some(b)) => Some(format!("Per {}", b)),
+                (_, None, None) => None,
+            },
+            Expr::Min(xs) | Expr::Max(xs) => {
+                let units: Vec<Option<String>> = xs.iter().map(|x| x.units(lookup)).collect();
+                match units.iter().flatten().next() {
+                    Some(first) if units.iter().flatten().any(|u| u != first) => Some("Mixed Units".into()),
+                    first => first.cloned(),
+                }
+            },
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary('+', _, _) | Expr::Binary('-', _, _) => 1,
+            Expr::Binary(_, _, _) => 2,
+            Expr::Neg(_) => 3,
+            _ => 4,
+        }
+    }
+}
+
+// The lowest frequency of `series`, or `None` if the frequency of any of them is not known.
+fn lowest_frequency(series: &[&TimeSeries]) -> Option<Frequency> {
+    series
+        .iter()
+        .map(|ts| ts.frequency())
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .min_by_key(|frequency| frequency.per_year().unwrap_or(u32::MAX))
+}
+
+// The value of an expression: a number, or a series on the dates of the series it depends on.
+enum Value {
+    Number(f64),
+    Series(Box<TimeSeries>),
+}
+
+// Apply `f` to the values of `operands` on the dates shared by the series among them. A number
+// applies on every date.
+fn combine<F: Fn(&[f64]) -> f64>(operands: Vec<Value>, f: F) -> Result<Value> {
+    let series: Vec<(String, TimeSeries)> = operands
+        .iter()
+        .enumerate()
+        .filter_map(|(k, operand)| match operand {
+            Value::Series(ts) => Some((k.to_string(), (**ts).clone())),
+            Value::Number(_) => None,
+        })
+        .collect();
+    if series.is_empty() {
+        let numbers: Vec<f64> = operands
+            .iter()
+            .filter_map(|operand| if let Value::Number(c) = operand { Some(*c) } else { None })
+            .collect();
+        return Ok(Value::Number(f(&numbers)))
+    }
+
+    let panel = Panel::join_named(series, Join::Inner)?;
+    let columns: Vec<Option<&[Option<f64>]>> = (0..operands.len())
+        .map(|k| panel.column(&k.to_string()).map(|ts| ts.values()))
+        .collect();
+    let values = (0..panel.len()).map(|i| {
+        let row = operands
+            .iter()
+            .zip(&columns)
+            .map(|(operand, column)| match (operand, column) {
+                (_, Some(column)) => column[i],
+                (Value::Number(c), None) => Some(*c),
+                (Value::Series(_), None) => None,
+            })
+            .collect::<Option<Vec<f64>>>()?;
+        Some(f(&row))
+    });
+    Ok(Value::Series(Box::new(TimeSeries::from_parts(panel.dates().to_vec(), finite(values), None))))
+}
+
+// Infinite and NaN values, such as from division by zero, are missing.
+fn finite<I: Iterator<Item = Option<f64>>>(values: I) -> Vec<Option<f64>> {
+    values.map(|v| v.filter(|v| v.is_finite())).collect()
+}
+
+impl FromStr for Formula {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Formula::parse(s)
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+// Written with the parentheses needed to parse it back.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter<'_>, x: &Expr, parens: bool| {
+            if parens { write!(f, "({})", x) } else { write!(f, "{}", x) }
+        };
+        let list = |xs: &[Expr]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ");
+        match self {
+            Expr::Number(c) => write!(f, "{}", c),
+            Expr::Series(id) => write!(f, "{}", id),
+            Expr::Neg(x) => {
+                write!(f, "-")?;
+                operand(f, x, x.precedence() < 3)
+            },
+            Expr::Binary(op, a, b) => {
+                operand(f, a, a.precedence() < self.precedence())?;
+                write!(f, " {} ", op)?;
+                let parens = match op {
+                    '-' | '/' => b.precedence() <= self.precedence(),
+                    _ => b.precedence() < self.precedence(),
+                };
+                operand(f, b, parens)
+            },
+            Expr::Log(x) => write!(f, "log({})", x),
+            Expr::Lag(x, n) => write!(f, "lag({}, {})", x, n),
+            Expr::Diff(x, n) => write!(f, "diff({}, {})", x, n),
+            Expr::Pct(x, n) => write!(f, "pct({}, {})", x, n),
+            Expr::Min(xs) => write!(f, "min({})", list(xs)),
+            Expr::Max(xs) => write!(f, "max({})", list(xs)),
+        }
+    }
+}
+
+impl FredClient {
+    /// Fetch the series a formula refers to and evaluate it.
+    pub fn formula(formula: &Formula) -> Result<TimeSeries> {
+        let series = formula
+            .series_ids()
+            .iter()
+            .map(FredClient::time_series)
+            .collect::<Result<Vec<_>>>()?;
+        formula.evaluate(&series)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Symbol(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Symbol(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            let number = number.parse().map_err(|_| anyhow!("Invalid number [{}] in formula [{}].", number, formula))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(anyhow!("Unexpected [{}] in formula [{}].", c, formula))
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens:                         Vec<Token>,
+    pos:                            usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| anyhow!("Formula ended unexpectedly."))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<()> {
+        match self.next()? {
+            Token::Symbol(c) if c == symbol => Ok(()),
+            token => Err(anyhow!("Expected [{}] but found [{}].", symbol, token)),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.binary(&['+', '-'], Parser::term)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        self.binary(&['*', '/'], Parser::unary)
+    }
+
+    fn binary(&mut self, ops: &[char], operand: fn(&mut Parser) -> Result<Expr>) -> Result<Expr> {
+        let mut expr = operand(self)?;
+        while let Some(&op) = ops.iter().find(|op| self.peek() == Some(&Token::Symbol(**op))) {
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(operand(self)?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next()? {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Symbol('(') => {
+                let expr = self.expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            },
+            Token::Ident(name) if self.eat('(') => self.call(&name),
+            // FRED series ids are upper case.
+            Token::Ident(name) => Ok(Expr::Series(SeriesId::new(&name.to_uppercase())?)),
+            token => Err(anyhow!("Unexpected [{}] in formula.", token)),
+        }
+    }
+
+    // The arguments and closing parenthesis of a call to `name`.
+    fn call(&mut self, name: &str) -> Result<Expr> {
+        let mut args = vec![self.expr()?];
+        while self.eat(',') {
+            args.push(self.expr()?);
+        }
+        self.expect(')')?;
+
+        let lag = |args: &[Expr], default: Option<usize>| -> Result<usize> {
+            match (args.get(1), default) {
+                (Some(Expr::Number(n)), _) if n.fract() == 0.0 && *n >= 0.0 => Ok(*n as usize),
+                (None, Some(default)) => Ok(default),
+                _ => Err(anyhow!("The second argument of [{}] must be a whole number of periods.", name)),
+            }
+        };
+        let arity = |min: usize, max: usize| match args.len() {
+            n if n >= min && n <= max => Ok(()),
+            n => Err(anyhow!("[{}] takes {} to {} arguments, found {}.", name, min, max, n)),
+        };
+        match name {
+            "log" => arity(1, 1).map(|_| Expr::Log(Box::new(args.swap_remove(0)))),
+            "lag" => {
+                arity(2, 2)?;
+                let n = lag(&args, None)?;
+                Ok(Expr::Lag(Box::new(args.swap_remove(0)), n))
+            },
+            "diff" | "pct" => {
+                arity(1, 2)?;
+                let n = lag(&args, Some(1))?;
+                let x = Box::new(args.swap_remove(0));
+                Ok(if name == "diff" { Expr::Diff(x, n) } else { Expr::Pct(x, n) })
+            },
+            "min" => Ok(Expr::Min(args)),
+            "max" => Ok(Expr::Max(args)),
+            _ => Err(anyhow!("Unknown function [{}].", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use crate::{Formula, SeriesItem, TimeSeries};
+
+    fn series(id: &str, units: &str, values: &[(u32, f64)]) -> TimeSeries {
+        let meta = SeriesItem {
+            units:                      units.into(),
+            units_short:                units.into(),
+            last_updated:               format!("2024-01-0{} 08:00:00-06", values.len()),
+            ..crate::test::series_item(id)
+        };
+        TimeSeries::new(
+            values.iter().map(|(m, v)| (NaiveDate::from_ymd_opt(2020, *m, 1).unwrap(), Some(*v))).collect()
+        ).unwrap().with_meta(meta)
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let formula = Formula::parse("(a/b)*100 - -lag(a, 2) + max(A, 1)").unwrap();
+        assert_eq!(formula.to_string(), "A / B * 100 - -lag(A, 2) + max(A, 1)");
+        assert_eq!(Formula::parse(&formula.to_string()).unwrap(), formula);
+        assert_eq!(Formula::parse("A - (B - C)").unwrap().to_string(), "A - (B - C)");
+        assert_eq!(Formula::parse("diff(A)").unwrap().to_string(), "diff(A, 1)");
+        assert_eq!(
+            formula.series_ids().iter().map(|id| id.as_str()).collect::<Vec<_>>(),
+            vec!["A", "B"],
+        );
+
+        for bad in &["", "1 + 2", "A +", "(A", "A B", "lag(A)", "lag(A, 1.5)", "foo(A)", "A $ B"] {
+            assert!(Formula::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn evaluate_aligns_series() {
+        let gdp = series("GDP", "Billions of Dollars", &[(1, 100.0), (2, 110.0), (3, 120.0), (4, 132.0)]);
+        let def = series("GDPDEF", "Billions of Dollars", &[(2, 55.0), (3, 60.0), (4, 66.0), (5, 70.0)]);
+
+        let ratio = Formula::parse("(GDP / GDPDEF) * 100").unwrap().evaluate(&[gdp.clone(), def.clone()]).unwrap();
+        assert_eq!(ratio.len(), 3);
+        assert_eq!(ratio.values(), &[Some(200.0), Some(200.0), Some(200.0)]);
+        let meta = ratio.meta.unwrap();
+        assert_eq!(meta.id.as_str(), "FORMULA_GDP_GDPDEF_100");
+        assert_eq!(meta.title, "GDP / GDPDEF * 100");
+        assert_eq!(meta.units, "Ratio");
+        assert_eq!(meta.observation_start, "2020-02-01");
+        assert_eq!(meta.last_updated, "2024-01-04 08:00:00-06");
+        assert!(meta.notes.unwrap().contains("GDPDEF: Series GDPDEF"));
+
+        let negated = Formula::parse("-GDP").unwrap().evaluate(std::slice::from_ref(&gdp)).unwrap();
+        assert_eq!(negated.meta.unwrap().id.as_str(), "FORMULA_GDP");
+
+        let growth = Formula::parse("pct(GDP)").unwrap().evaluate(std::slice::from_ref(&gdp)).unwrap();
+        assert_eq!(growth.values()[0], None);
+        assert!((growth.values()[3].unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(growth.meta.unwrap().units, "Percent Change");
+
+        let change = Formula::parse("diff(GDP, 2) - lag(GDP, 1) * 0").unwrap().evaluate(std::slice::from_ref(&gdp)).unwrap();
+        assert_eq!(change.values()[..2], [None, None]);
+        assert!((change.values()[2].unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(change.meta.unwrap().units, "Billions of Dollars");
+
+        let bounded = Formula::parse("min(max(GDPDEF, 55), 60)").unwrap().evaluate(std::slice::from_ref(&def)).unwrap();
+        assert_eq!(bounded.values(), &[Some(55.0), Some(60.0), Some(60.0), Some(60.0)]);
+
+        let log = Formula::parse("log(GDP - 100)").unwrap().evaluate(std::slice::from_ref(&gdp)).unwrap();
+        assert_eq!(log.values()[0], None);
+        assert_eq!(log.meta.unwrap().units, "Natural Log of Billions of Dollars");
+
+        assert!(Formula::parse("GDP + UNRATE").unwrap().evaluate(&[gdp]).is_err());
+    }
+
+    #[test]
+    fn mixed_frequencies_are_averaged_to_the_lowest() {
+        // Business days of the first quarter of 2020, valued one more than the month.
+        let gs10 = TimeSeries::new(
+            (0..91)
+                .map(|i| NaiveDate::from_ymd_opt(2020, 1, 1).unwrap() + chrono::Duration::days(i))
+                .filter(|d| chrono::Datelike::weekday(d).num_days_from_monday() < 5)
+                .map(|d| (d, Some(chrono::Datelike::month(&d) as f64 + 1.0)))
+                .collect()
+        ).unwrap().with_meta(SeriesItem { frequency: "Daily".into(), ..crate::test::series_item("GS10") });
+        let fedfunds = series("FEDFUNDS", "Percent", &[(1, 1.0), (2, 2.0), (3, 3.0)]);
+
+        let spread = Formula::parse("GS10 - FEDFUNDS").unwrap().evaluate(&[gs10.clone(), fedfunds]).unwrap();
+        assert_eq!(spread.values(), &[Some(1.0), Some(1.0), Some(1.0)]);
+        assert_eq!(spread.meta.unwrap().frequency, "Monthly");
+
+        let level = Formula::parse("GS10 * 1").unwrap().evaluate(&[gs10]).unwrap();
+        assert_eq!(level.len(), 65);
+        assert_eq!(level.meta.unwrap().frequency, "Daily");
+    }
+
+    #[test]
+    fn lags_count_periods_of_each_series() {
+        let gdp = series("GDP", "Billions of Dollars", &[(1, 100.0), (2, 110.0), (3, 121.0), (4, 133.1)]);
+        let def = series("GDPDEF", "Index", &[(2, 55.0), (3, 60.0), (4, 66.0), (5, 70.0)]);
+
+        // February growth is from January, which GDPDEF does not have.
+        let growth = Formula::parse("pct(GDP) + GDPDEF * 0").unwrap().evaluate(&[gdp.clone(), def.clone()]).unwrap();
+        assert_eq!(growth.dates(), &def.dates()[..3]);
+        assert!(growth.values().iter().all(|v| (v.unwrap() - 10.0).abs() < 1e-9));
+
+        // GDPDEF lagged by one period starts in March.
+        let lagged = Formula::parse("GDP - lag(GDPDEF, 1)").unwrap().evaluate(&[gdp, def]).unwrap();
+        assert_eq!(lagged.values()[..2], [None, Some(66.0)]);
+        assert!((lagged.values()[2].unwrap() - 73.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn series_ids_are_not_case_sensitive() {
+        let gdp = series("GDP", "Billions of Dollars", &[(1, 100.0), (2, 110.0)]);
+        let def = series("gdpdef", "Billions of Dollars", &[(1, 50.0), (2, 55.0)]);
+
+        let ratio = Formula::parse("gdp/gdpdef").unwrap().evaluate(&[gdp, def]).unwrap();
+        assert_eq!(ratio.values(), &[Some(2.0), Some(2.0)]);
+        assert_eq!(ratio.meta.unwrap().title, "GDP / GDPDEF");
+    }
+}
@@ -0,0 +1,274 @@
+//! Strongly typed identifiers. Ids returned in one response can be passed directly to the next
+//! request.
+//!
+//! ```
+//! let series = FredClient::series(&SeriesId::new("GNPCA")?)?;
+//! let categories = FredClient::series_categories(&series.seriess.inner()[0].id)?;
+//! let children = FredClient::category_children(categories.categories[0].id)?;
+//! ```
+
+use anyhow::{anyhow, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fmt,
+    str::FromStr,
+};
+
+// FRED is not consistent about numeric ids, for example `release_id` is a string in release
+// tables and a number everywhere else, so we accept both.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumericRepr {
+    Number(usize),
+    Text(String),
+}
+
+macro_rules! numeric_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+        #[serde(try_from = "NumericRepr", into = "usize")]
+        pub struct $name(usize);
+
+        impl $name {
+            pub fn new(id: usize) -> Self {
+                $name(id)
+            }
+
+            pub fn value(&self) -> usize {
+                self.0
+            }
+        }
+
+        impl From<usize> for $name {
+            fn from(id: usize) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                s.trim()
+                    .parse::<usize>()
+                    .map($name)
+                    .map_err(|_| {
+                        anyhow!("Expected {} to be an integer, found [{}].", stringify!($name), s)
+                    })
+            }
+        }
+
+        impl TryFrom<NumericRepr> for $name {
+            type Error = Error;
+
+            fn try_from(repr: NumericRepr) -> Result<Self> {
+                match repr {
+                    NumericRepr::Number(n) => Ok($name(n)),
+                    NumericRepr::Text(s) => s.parse(),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    }
+}
+
+numeric_id!(
+    /// A category id. The root category is `CategoryId::new(0)`.
+    CategoryId
+);
+
+numeric_id!(
+    /// A release id.
+    ReleaseId
+);
+
+numeric_id!(
+    /// A source id.
+    SourceId
+);
+
+numeric_id!(
+    /// The id of an element in a release table.
+    ElementId
+);
+
+/// A series id such as `GNPCA`. Must be non-empty and contain only ASCII letters, digits and
+/// underscores.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SeriesId(String);
+
+impl SeriesId {
+    pub fn new(id: &str) -> Result<Self> {
+        let id = id.trim();
+        if id.is_empty() {
+            return Err(anyhow!("Series id was empty."))
+        }
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(anyhow!("Series id [{}] contains invalid characters.", id))
+        }
+        Ok(SeriesId(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for SeriesId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        SeriesId::new(s)
+    }
+}
+
+impl TryFrom<String> for SeriesId {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        SeriesId::new(&s)
+    }
+}
+
+impl TryFrom<&str> for SeriesId {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        SeriesId::new(s)
+    }
+}
+
+impl From<SeriesId> for String {
+    fn from(id: SeriesId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<str> for SeriesId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SeriesId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A tag name such as `usa` or `price index`. Must be non-empty and must not contain `;`, which
+/// FRED uses to separate tag names in a request.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TagName(String);
+
+impl TagName {
+    pub fn new(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Tag name was empty."))
+        }
+        if name.contains(';') {
+            return Err(anyhow!("Tag name [{}] contains ';'.", name))
+        }
+        Ok(TagName(name.to_string()))
+    }
+
+    /// Parse a `;` separated list such as `"cpi;usa;nation"`.
+    pub fn parse_list(names: &str) -> Result<Vec<Self>> {
+        names.split(';').map(TagName::new).collect()
+    }
+
+    /// Join tag names into the `;` separated form used in requests.
+    pub fn join(names: &[TagName]) -> String {
+        names.iter().map(|name| name.as_str()).collect::<Vec<&str>>().join(";")
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for TagName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        TagName::new(s)
+    }
+}
+
+impl TryFrom<String> for TagName {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        TagName::new(&s)
+    }
+}
+
+impl TryFrom<&str> for TagName {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        TagName::new(s)
+    }
+}
+
+impl From<TagName> for String {
+    fn from(name: TagName) -> Self {
+        name.0
+    }
+}
+
+impl AsRef<str> for TagName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TagName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CategoryId, ReleaseId, SeriesId, TagName};
+
+    #[test]
+    fn ids_validate() {
+        assert!(SeriesId::new("LRUNTTTTAUM156S").is_ok());
+        assert!(SeriesId::new("").is_err());
+        assert!(SeriesId::new("GDP;CPI").is_err());
+        assert!(TagName::new("price index").is_ok());
+        assert!(TagName::new("usa;nation").is_err());
+        assert_eq!(
+            TagName::join(&TagName::parse_list("cpi;usa;nation").unwrap()),
+            "cpi;usa;nation",
+        );
+        assert_eq!("478".parse::<ReleaseId>().unwrap(), ReleaseId::new(478));
+    }
+
+    #[test]
+    fn numeric_ids_deserialize_from_numbers_and_strings() {
+        let from_number: ReleaseId = serde_json::from_str("53").unwrap();
+        let from_string: ReleaseId = serde_json::from_str("\"53\"").unwrap();
+        assert_eq!(from_number, from_string);
+        assert!(serde_json::from_str::<CategoryId>("\"abc\"").is_err());
+        assert_eq!(serde_json::to_string(&from_string).unwrap(), "53");
+    }
+}
@@ -6,12 +6,12 @@
 //!
 //! To make a single request,
 //! ```
-//! let series_tags = FredClient::series_tags("JPNCPIALLMINMEI").unwrap();
+//! let series_tags = FredClient::series_tags(&SeriesId::new("JPNCPIALLMINMEI")?).unwrap();
 //! ```
 //!
 //! To make multiple pipelined requests,
 //! ```
-//! let iter = FredClientIter::new(vec![1, 2, 3].iter(), |id| category_id(id));
+//! let iter = FredClientIter::new(vec![1, 2, 3].into_iter(), |id| FredClient::category(CategoryId::new(id)));
 //! let responses = FredClient::pipeline(iter);
 //! ```
 
@@ -25,12 +25,62 @@ use key_tree::serialize::{
     KeyTreeString,
     IntoKeyTree,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde::de::DeserializeOwned;
+use reqwest::StatusCode;
 use std::{
     env,
     fmt::{Display, self},
     iter::Iterator,
+    sync::OnceLock,
+    thread,
+    time::Duration,
+};
+
+mod alfred;
+mod bulk;
+mod calendar;
+mod catalog;
+mod crawler;
+mod formula;
+mod ids;
+pub mod maps;
+mod mirror;
+mod panel;
+mod realtime;
+mod revisions;
+mod search;
+mod timeseries;
+mod vintage;
+mod watch;
+
+pub use alfred::FirstRelease;
+pub use bulk::{BulkObservation, ReleaseObservationsIter, ReleaseObservationsPage, SeriesBlock, V2Request};
+pub use calendar::{CalendarQuery, ReleaseCalendar};
+pub use catalog::{Catalog, CatalogRefresh};
+pub use crawler::{CategoryCrawler, CategoryNode, CrawledCategory, CrawlState, RateLimiter};
+pub use formula::Formula;
+pub use ids::{CategoryId, ElementId, ReleaseId, SeriesId, SourceId, TagName};
+pub use mirror::{Mirror, MirroredSeries, SyncReport};
+pub use panel::{Join, Panel};
+pub use realtime::{LateHistory, RealTimeDataset, RealTimePanel, Snapshot};
+pub use revisions::{Revision, RevisionAnalysis, RevisionSummary};
+pub use search::SearchQuery;
+pub use timeseries::{Aggregation, Fill, Gap, Incomplete, Rolling, TimeSeries, Transformation, Window};
+pub use vintage::{VintageColumn, VintageMatrix, VintageObservations, VintageOutput};
+pub use watch::{
+    ChangeEvent,
+    ChangeKind,
+    JsonLinesSink,
+    SeenSeries,
+    Sink,
+    SinkConfig,
+    StdoutSink,
+    WatchConfig,
+    WatchPoll,
+    WatchState,
+    Watcher,
+    WebhookSink,
 };
 
 /// An iterator that makes a series of API requests.
@@ -38,13 +88,13 @@ use std::{
 /// To make a group of requests we can build a `FredClientIter` from a iterator over request
 /// arguments and specify a request function on those argyments, for example
 /// ```
-/// let iter = FredClientIter::new(vec![1, 2, 3].iter(), |id| category_id(id));
+/// let iter = FredClientIter::new(vec![1, 2, 3].into_iter(), |id| FredClient::category(CategoryId::new(id)));
 /// ```
 /// If there are multiple arguments we can do
 /// ```
 /// let iter = FredClientIter::new(
-///     vec![(1, "tag1"), (2, "tag2")].iter(),
-///     |(id, tag)| category_related_tags(id, tag),
+///     vec![(1, "tag1"), (2, "tag2")].into_iter(),
+///     |(id, tag)| FredClient::category_related_tags(CategoryId::new(id), &[TagName::new(tag)?]),
 /// );
 /// ```
 pub struct FredClientIter<ArgsIter, F, U>
@@ -91,41 +141,41 @@ impl FredClient {
     /// [Get a category](https://fred.stlouisfed.org/docs/api/fred/category.html)
     /// ```
     /// for i in 0..100 {
-    ///     match Fred::category(i) {
+    ///     match Fred::category(CategoryId::new(i)) {
     ///         Ok(category) => println!("{}\n{}", i, category),
     ///         Err(json_err) => {},
     ///     }
     /// }
     /// ```
-    pub fn category(category_id: usize) -> Result<Categories> { 
+    pub fn category(category_id: CategoryId) -> Result<Categories> { 
         req(FredRequest::new("category", vec![("category_id", category_id)])?)
     }
 
     /// [Get the child categories for a specified parent category.](https://fred.stlouisfed.org/docs/api/fred/category_children.html)
-    pub fn category_children(category_id: usize) -> Result<Categories> {
+    pub fn category_children(category_id: CategoryId) -> Result<Categories> {
         req(FredRequest::new("category/children", vec![("category_id", category_id)])?)
     }
     
     /// [Get the related categories for a category.](https://fred.stlouisfed.org/docs/api/fred/category_related.html)
-    pub fn category_related(category_id: usize) -> Result<Categories> {
+    pub fn category_related(category_id: CategoryId) -> Result<Categories> {
         req(FredRequest::new("category/related", vec![("category_id", category_id)])?)
     }
     
     /// [Get the series in a category.](https://fred.stlouisfed.org/docs/api/fred/category_series.html)
-    pub fn category_series(category_id: usize) -> Result<CategorySeries> {
+    pub fn category_series(category_id: CategoryId) -> Result<CategorySeries> {
         req(FredRequest::new("category/series", vec![("category_id", category_id)])?)
     }
     
     /// [Get the tags for a category.](https://fred.stlouisfed.org/docs/api/fred/category_tags.html)
-    pub fn category_tags(category_id: usize) -> Result<CategoryTags> {
+    pub fn category_tags(category_id: CategoryId) -> Result<CategoryTags> {
         req(FredRequest::new("category/tags", vec![("category_id", category_id)])?)
     }
     
     /// [Get the related tags for a category.](https://fred.stlouisfed.org/docs/api/fred/category_related_tags.html)
-    pub fn category_related_tags<T: std::fmt::Display>(category_id: T, tag_names: T) -> Result<CategoryRelatedTags> {
+    pub fn category_related_tags(category_id: CategoryId, tag_names: &[TagName]) -> Result<CategoryRelatedTags> {
         req(FredRequest::new(
             "category/related_tags",
-            vec![("category_id", category_id), ("tag_names", tag_names)],
+            vec![("category_id", category_id.to_string()), ("tag_names", TagName::join(tag_names))],
         )?)
     }
 
@@ -143,70 +193,89 @@ impl FredClient {
     }
 
     /// [Get a release of economic data.](https://fred.stlouisfed.org/docs/api/fred/release.html)
-    pub fn release(release_id: usize) -> Result<Release> { 
+    pub fn release(release_id: ReleaseId) -> Result<Release> { 
         req(FredRequest::new( "release", vec![("release_id", release_id)])?)
     }
 
     /// [Get release dates for a release of economic data.](https://fred.stlouisfed.org/docs/api/fred/release_dates.html)
-    pub fn release_dates(release_id: usize) -> Result<ReleaseDates> { 
+    pub fn release_dates(release_id: ReleaseId) -> Result<ReleaseDates> { 
         req(FredRequest::new("release/dates", vec![("release_id", release_id)])?)
     }
 
     /// [Get the series on a release of economic data.](https://fred.stlouisfed.org/docs/api/fred/release_series.html)
-    pub fn release_series(release_id: usize) -> Result<ReleaseSeries> { 
+    pub fn release_series(release_id: ReleaseId) -> Result<ReleaseSeries> { 
         req(FredRequest::new("release/series", vec![("release_id", release_id)])?)
     }
 
     /// [Get the sources for a release of economic data.](https://fred.stlouisfed.org/docs/api/fred/release_sources.html)
-    pub fn release_sources(release_id: usize) -> Result<ReleaseSources> { 
+    pub fn release_sources(release_id: ReleaseId) -> Result<ReleaseSources> { 
         req(FredRequest::new("release/sources", vec![("release_id", release_id)])?)
     }
 
     /// [Get the tags for a release.](https://fred.stlouisfed.org/docs/api/fred/release_tags.html)
-    pub fn release_tags(release_id: usize) -> Result<ReleaseTags> { 
+    pub fn release_tags(release_id: ReleaseId) -> Result<ReleaseTags> { 
         req(FredRequest::new("release/tags", vec![("release_id", release_id)])?)
     }
 
     /// [Get the related tags for a release.](https://fred.stlouisfed.org/docs/api/fred/release_related_tags.html)
-    pub fn release_related_tags<T: std::fmt::Display>(release_id: T, tag_names: T) -> Result<ReleaseRelatedTags> { 
+    pub fn release_related_tags(release_id: ReleaseId, tag_names: &[TagName]) -> Result<ReleaseRelatedTags> { 
         req(FredRequest::new(
             "release/related_tags",
-            vec![("release_id", release_id), ("tag_names", tag_names)],
+            vec![("release_id", release_id.to_string()), ("tag_names", TagName::join(tag_names))],
         )?)
     }
 
     /// [Get the release tables for a given release.](https://fred.stlouisfed.org/docs/api/fred/release_tables.html)
-    pub fn release_tables(release_id: usize) -> Result<ReleaseTables> { 
+    pub fn release_tables(release_id: ReleaseId) -> Result<ReleaseTables> { 
         req(FredRequest::new("release/tables", vec![("release_id", release_id)])?)
     }
 
     /// [Get an economic data series.](https://fred.stlouisfed.org/docs/api/fred/series.html)
-    pub fn series(series_id: &str) -> Result<Series> { 
+    pub fn series(series_id: &SeriesId) -> Result<Series> { 
         req(FredRequest::new("series", vec![("series_id", series_id)])?)
     }
 
     /// Return the series request as JSON. 
-    pub fn series_json(series_id: &str) -> Result<String> {
+    pub fn series_json(series_id: &SeriesId) -> Result<String> {
         req(FredRequest::new("series", vec![("series_id", series_id)])?)
     }
 
     /// [Get the categories for an economic data series.](https://fred.stlouisfed.org/docs/api/fred/series_categories.html)
-    pub fn series_categories(series_id: &str) -> Result<Categories> { 
+    pub fn series_categories(series_id: &SeriesId) -> Result<Categories> { 
         req(FredRequest::new("series/categories", vec![("series_id", series_id)])?)
     }
 
     /// [Get the observations or data values for an economic data series.](https://fred.stlouisfed.org/docs/api/fred/series_observations.html)
-    pub fn series_observations(series_id: &str) -> Result<SeriesObservations> { 
+    pub fn series_observations(series_id: &SeriesId) -> Result<SeriesObservations> { 
         req(FredRequest::new("series/observations", vec![("series_id", series_id)])?)
     }
 
     /// Return the series_observations request as JSON. 
-    pub fn series_observations_json(series_id: &str) -> Result<String> {
+    pub fn series_observations_json(series_id: &SeriesId) -> Result<String> {
         req(FredRequest::new("series/observations", vec![("series_id", series_id)])?)
     }
 
+    /// [Get the observations for an economic data series by vintage date.](https://fred.stlouisfed.org/docs/api/fred/series_observations.html)
+    /// Use `realtime_start` of `1776-07-04` and `realtime_end` of `9999-12-31` for all vintages.
+    pub fn series_observations_by_vintage(
+        series_id: &SeriesId,
+        output_type: VintageOutput,
+        realtime_start: &str,
+        realtime_end: &str) -> Result<VintageObservations>
+    {
+        req(FredRequest::new(
+            "series/observations",
+            vec![
+                ("series_id", series_id.to_string()),
+                ("output_type", output_type.code().to_string()),
+                ("realtime_start", realtime_start.to_string()),
+                ("realtime_end", realtime_end.to_string()),
+            ],
+        )?)
+    }
+
     /// [Get the release for an economic data series.](https://fred.stlouisfed.org/docs/api/fred/series_release.html)
-    pub fn series_release(series_id: &str) -> Result<SeriesRelease>{ 
+    pub fn series_release(series_id: &SeriesId) -> Result<SeriesRelease>{ 
         req(FredRequest::new("series/release", vec![("series_id", series_id)])?)
     }
 
@@ -221,19 +290,19 @@ impl FredClient {
     }
 
     /// [Get the related tags for a series search.](https://fred.stlouisfed.org/docs/api/fred/series_search_related_tags.html)
-    pub fn series_search_related_tags(series_search_text: &str, tag_names: &str) -> Result<SeriesSearchRelatedTags> { 
+    pub fn series_search_related_tags(series_search_text: &str, tag_names: &[TagName]) -> Result<SeriesSearchRelatedTags> { 
         req(FredRequest::new(
             "series/search/related_tags",
-            vec![("series_search_text", series_search_text), ("tag_names", tag_names)],
+            vec![("series_search_text", series_search_text.to_string()), ("tag_names", TagName::join(tag_names))],
         )?)
     }
 
     /// [Get the tags for an economic data series.](https://fred.stlouisfed.org/docs/api/fred/series_tags.html)
     /// ```
-    /// let series_tags = Fred::series_tags("JPNCPIALLMINMEI")
+    /// let series_tags = Fred::series_tags(&SeriesId::new("JPNCPIALLMINMEI")?)
     ///     .unwrap_or_else{|e| eprintln!(e)};
     /// ```
-    pub fn series_tags(series_id: &str) -> Result<SeriesTags> { 
+    pub fn series_tags(series_id: &SeriesId) -> Result<SeriesTags> { 
         req(FredRequest::new("series/tags", vec![("series_id", series_id)])?)
     }
 
@@ -243,7 +312,7 @@ impl FredClient {
     }
 
     /// [Get the dates in history when a series' data values were revised or new data values were released.](https://fred.stlouisfed.org/docs/api/fred/series_vintagedates.html)
-    pub fn series_vintagedates(series_id: &str) -> Result<SeriesVintageDates> { 
+    pub fn series_vintagedates(series_id: &SeriesId) -> Result<SeriesVintageDates> { 
         req(FredRequest::new("series/vintagedates", vec![("series_id", series_id)])?)
     }
 
@@ -253,12 +322,12 @@ impl FredClient {
     }
 
     /// [Get a source of economic data.](https://fred.stlouisfed.org/docs/api/fred/source.html)
-    pub fn source(source_id: usize) -> Result<ReleaseSources> { 
+    pub fn source(source_id: SourceId) -> Result<ReleaseSources> { 
         req(FredRequest::new("source", vec![("source_id", source_id)])?)
     }
 
     /// [Get the releases for a source.](https://fred.stlouisfed.org/docs/api/fred/source_releases.html)
-    pub fn source_releases(source_id: usize) -> Result<SourceReleases> { 
+    pub fn source_releases(source_id: SourceId) -> Result<SourceReleases> { 
         req(FredRequest::new("source/releases", vec![("source_id", source_id)])?)
     }
 
@@ -268,24 +337,63 @@ impl FredClient {
     }
 
     /// [Get the related tags for one or more tags.](https://fred.stlouisfed.org/docs/api/fred/related_tags.html)
-    pub fn related_tags(tag_names: &str) -> Result<Tags> { 
-        req(FredRequest::new("related_tags", vec![("tag_names", tag_names)])?)
+    pub fn related_tags(tag_names: &[TagName]) -> Result<Tags> { 
+        req(FredRequest::new("related_tags", vec![("tag_names", TagName::join(tag_names))])?)
     }
 
     /// [Get the series matching tags.](https://fred.stlouisfed.org/docs/api/fred/tags_series.html)
     /// ```
-    /// match Fred::tags_series("korea") {
+    /// match Fred::tags_series(&[TagName::new("korea")?]) {
     ///     Ok(tags_series) => println!("{}", tags_series),
     ///     Err(json_err) => println!("{}", json_err),
     /// };
     /// ```
     /// or
     /// ``` 
-    /// Fred::tags_series("cpi;usa;nation").unwrap().to_string()
+    /// Fred::tags_series(&TagName::parse_list("cpi;usa;nation")?).unwrap().to_string()
     /// ```
-    pub fn tags_series(tag_names: &str) -> Result<TagsSeries> { 
-        req(FredRequest::new( "tags/series", vec![("tag_names", tag_names)])?)
+    pub fn tags_series(tag_names: &[TagName]) -> Result<TagsSeries> { 
+        req(FredRequest::new( "tags/series", vec![("tag_names", TagName::join(tag_names))])?)
    } 
+
+    /// Make a request to any endpoint under `fred/` with arbitrary parameters, for endpoints or
+    /// parameters that don't have a method yet. As for every other request, the API key and file
+    /// type are added, values are percent-encoded, and the request is rate limited and retried.
+    /// ```
+    /// let json = FredClient::raw(
+    ///     "series/observations",
+    ///     &[("series_id", "GNPCA"), ("units", "pch"), ("sort_order", "desc")],
+    /// )?;
+    /// ```
+    pub fn raw<K: Display, V: Display>(endpoint: &str, params: &[(K, V)]) -> Result<serde_json::Value> {
+        req(FredRequest::from_pairs(endpoint, params))
+    }
+
+    /// As `raw()` but coerces the response into any deserializable type.
+    /// ```
+    /// let obs: SeriesObservations = FredClient::raw_as(
+    ///     "series/observations",
+    ///     &[("series_id", "GNPCA"), ("observation_start", "2000-01-01")],
+    /// )?;
+    /// ```
+    pub fn raw_as<U, K, V>(endpoint: &str, params: &[(K, V)]) -> Result<U>
+    where
+        U: DeserializeOwned,
+        K: Display,
+        V: Display,
+    {
+        req(FredRequest::from_pairs(endpoint, params))
+    }
+
+    /// Make a request built from any `IntoRequest` and coerce the response into any
+    /// deserializable type.
+    pub fn request<R, U>(into_req: R) -> Result<U>
+    where
+        R: IntoRequest,
+        U: DeserializeOwned,
+    {
+        req(into_req)
+    }
 }
 
 // This is the plumbing for the Fred API request functions. 
@@ -318,12 +426,44 @@ where
     serde_json::from_str(&response).context(format!("Failed to parse [{}]", response))
 }
 
-/// Construct a request and return the response.
+// FRED allows 120 requests a minute per API key.
+const REQUESTS_PER_MINUTE: u32 = 120;
+
+// Failed requests are retried this many times, waiting one, two, then four seconds.
+const RETRIES: u32 = 3;
+
+// The limiter shared by all requests.
+fn limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| RateLimiter::new(REQUESTS_PER_MINUTE))
+}
+
+/// Construct a request and return the response. Requests are rate limited, and retried with
+/// backoff on a connection error, a server error or `429 Too Many Requests`.
 fn response<R: IntoRequest>(into_req: R) -> Result<String> {
 
     let req: String = into_req.into_request()?;
-
-    let  blocking_response = reqwest::blocking::get(&req)?;
+    let token = into_req.bearer_token()?;
+
+    let mut attempt = 0;
+    let blocking_response = loop {
+        limiter().wait();
+        let result = match &token {
+            Some(token) => reqwest::blocking::Client::new().get(&req).bearer_auth(token).send(),
+            None => reqwest::blocking::get(&req),
+        };
+        let retry = match &result {
+            Ok(response) => {
+                response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS
+            },
+            Err(_) => true,
+        };
+        if !retry || attempt == RETRIES {
+            break result?
+        }
+        thread::sleep(Duration::from_secs(1 << attempt));
+        attempt += 1;
+    };
 
     let response = blocking_response.text_with_charset("utf-8")?;
 
@@ -361,6 +501,12 @@ pub trait IntoRequest {
     fn into_request(&self) -> Result<String>;
     fn base_url(&self) -> Result<String>;
     fn api_key(&self) -> Result<String>;
+
+    /// A token to send in an `Authorization: Bearer` header, for requests that don't take the API
+    /// key as a parameter.
+    fn bearer_token(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 impl IntoRequest for FredRequest {
@@ -411,7 +557,7 @@ impl FredRequest {
     // }
     
     pub fn new<T: Display>(url: &str, keyvals: Vec<(&'static str, T)>) -> Result<Self> {
-        let kvs = keyvals.iter().map(|(key, val)| format!("{}={}", key, val)).collect(); 
+        let kvs = keyvals.iter().map(|(key, val)| encode_keyval(key, val)).collect();
         Ok(
             FredRequest {
                 url:        url.into(),
@@ -421,6 +567,15 @@ impl FredRequest {
         )
     }
 
+    /// Build a request from key-value pairs whose keys need not be `'static`.
+    pub fn from_pairs<K: Display, V: Display>(url: &str, keyvals: &[(K, V)]) -> Self {
+        FredRequest {
+            url:        url.into(),
+            keyvals:    keyvals.iter().map(|(key, val)| encode_keyval(key, val)).collect(),
+            format:     Format::Json,
+        }
+    }
+
     fn concat_keyvals(&self, sep: char) -> String {
         let mut s = String::new();
         for kv in &self.keyvals {
@@ -432,9 +587,27 @@ impl FredRequest {
     }
 }
 
+// A `key=value` query string pair with both parts percent-encoded, so that values containing `&`,
+// `=`, `;` or spaces are sent intact.
+fn encode_keyval<K: Display, V: Display>(key: K, val: V) -> String {
+    format!("{}={}", encode(&key.to_string()), encode(&val.to_string()))
+}
+
+// Percent-encode everything but the unreserved characters of RFC 3986.
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
 // Response data-structures ///////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Categories {
     pub categories: Vec<Category>,
 }
@@ -451,11 +624,11 @@ impl fmt::Display for Categories {
 
 /// See [Fred docs: /fred/category](https://fred.stlouisfed.org/docs/api/fred/category.html).
 ///
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Category {
-    pub id:                         usize,
+    pub id:                         CategoryId,
     pub name:                       String,
-    pub parent_id:                  usize,
+    pub parent_id:                  CategoryId,
     pub notes:                      Option<String>,
 }
 
@@ -472,13 +645,13 @@ impl fmt::Display for Category {
 }
 
 /// See [Fred docs: /fred/category/children](https://fred.stlouisfed.org/docs/api/fred/category_children.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CategoryChildren {
     pub categories: Vec<Category>,
 }
 
 /// See [Fred docs: /fred/category/related](https://fred.stlouisfed.org/docs/api/fred/category_related.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CategoryRelated {
     #[allow(dead_code)]
     categories: Vec<Category>,
@@ -502,7 +675,7 @@ impl<'a> Iterator for SeriesItemsIter<'a> {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesItems(Vec<SeriesItem>);
 
 impl SeriesItems {
@@ -557,6 +730,39 @@ impl SeriesItems {
         SeriesItems(v)
     }
 
+    /// Only include series with frequency `frequency`.
+    pub fn with_frequency(&self, frequency: &Frequency) -> SeriesItems {
+        let mut v = Vec::new();
+        for series in self.iter() {
+            if frequency.matches(&series.frequency_kind()) {
+                v.push(series.clone());
+            }
+        }
+        SeriesItems(v)
+    }
+
+    /// Only include series with seasonal adjustment `sa`.
+    pub fn with_seasonal_adjustment(&self, sa: &SeasonalAdjustment) -> SeriesItems {
+        let mut v = Vec::new();
+        for series in self.iter() {
+            if series.seasonal_adjustment_kind() == *sa {
+                v.push(series.clone());
+            }
+        }
+        SeriesItems(v)
+    }
+
+    /// Only include series with units `units`.
+    pub fn with_units(&self, units: &Units) -> SeriesItems {
+        let mut v = Vec::new();
+        for series in self.iter() {
+            if series.units_kind() == *units {
+                v.push(series.clone());
+            }
+        }
+        SeriesItems(v)
+    }
+
     pub fn inner(&self) -> Vec<SeriesItem> {
         (*self.0).to_vec()
     }
@@ -587,11 +793,12 @@ impl fmt::Display for SeriesItems {
 }
 
 /// See Fred docs
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CategorySeries {
     pub realtime_start:             String,
     pub realtime_end:               String,
     pub order_by:                   String,
+    pub sort_order:                 String,
     pub count:                      isize,
     pub offset:                     isize,
     pub limit:                      isize,
@@ -612,7 +819,7 @@ impl fmt::Display for CategorySeries {
 }
 
 /// See [Fred docs: /fred/category/tags](https://fred.stlouisfed.org/docs/api/fred/category_tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CategoryTags {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -624,9 +831,9 @@ pub struct CategoryTags {
     pub tags:                       Vec<Tag>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Tag {
-    pub name:                       String,
+    pub name:                       TagName,
     pub group_id:                   String,
     pub notes:                      Option<String>,
     pub created:                    String,
@@ -646,7 +853,7 @@ impl fmt::Display for Tag {
 }
 
 /// See [Fred docs: /fred/category/related_tags](https://fred.stlouisfed.org/docs/api/fred/category_related_tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CategoryRelatedTags {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -659,7 +866,7 @@ pub struct CategoryRelatedTags {
 }
 
 /// See [Fred docs: /fred/releases](https://fred.stlouisfed.org/docs/api/fred/releases.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Releases {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -672,16 +879,16 @@ pub struct Releases {
 }
 
 /// See [Fred docs: /fred/release](https://fred.stlouisfed.org/docs/api/fred/release.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Release {
     pub realtime_start:             String,
     pub realtime_end:               String,
     pub releases:                   Vec<ReleaseItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseItem {
-    pub id:                         isize,
+    pub id:                         ReleaseId,
     pub realtime_start:             String,
     pub realtime_end:               String,
     pub name:                       String,
@@ -690,7 +897,7 @@ pub struct ReleaseItem {
 }
 
 /// See [Fred docs: /fred/release/dates](https://fred.stlouisfed.org/docs/api/fred/release_dates.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleasesDates {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -702,7 +909,7 @@ pub struct ReleasesDates {
     pub release_dates:              Vec<ReleaseDate>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseDates {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -714,21 +921,21 @@ pub struct ReleaseDates {
     pub release_dates:              Vec<ReleaseDateItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseDateItem {
-    pub release_id:                 isize,
+    pub release_id:                 ReleaseId,
     pub date:                       String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseDate {
-    pub release_id:                 isize,
+    pub release_id:                 ReleaseId,
     pub release_name:               String,
     pub date:                       String,
 }
 
 /// See [Fred docs: /fred/release/series](https://fred.stlouisfed.org/docs/api/fred/release_series.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseSeries {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -741,16 +948,16 @@ pub struct ReleaseSeries {
 }
 
 /// See [Fred docs: /fred/release/sources](https://fred.stlouisfed.org/docs/api/fred/release_sources.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseSources {
     pub realtime_start:             String,
     pub realtime_end:               String,
     pub sources:                    Vec<SourceItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SourceItem {
-    pub id:                         isize,
+    pub id:                         SourceId,
     pub realtime_start:             String,
     pub realtime_end:               String,
     pub name:                       String,
@@ -758,7 +965,7 @@ pub struct SourceItem {
 }
 
 /// See [Fred docs: /fred/release/tags](https://fred.stlouisfed.org/docs/api/fred/release_tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseTags {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -770,9 +977,9 @@ pub struct ReleaseTags {
     pub tags:                       Vec<TagItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct TagItem {
-    pub name:                       String,
+    pub name:                       TagName,
     pub group_id:                   String,
     pub notes:                      Option<String>,
     pub created:                    String,
@@ -792,7 +999,7 @@ impl fmt::Display for TagItem {
 }
 
 /// See [Fred docs: /fred/release/related_tags](https://fred.stlouisfed.org/docs/api/fred/release_related_tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseRelatedTags {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -804,29 +1011,40 @@ pub struct ReleaseRelatedTags {
     pub tags:                       Vec<TagItem>,
 }
 
-/// See [Fred docs: /fred/release/related_tags](https://fred.stlouisfed.org/docs/api/fred/release_related_tags.html).
-#[derive(Debug, Deserialize)]
+/// See [Fred docs: /fred/release/tables](https://fred.stlouisfed.org/docs/api/fred/release_tables.html).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseTables {
     pub name:                       String,
-    pub element_id:                 isize,
-    pub release_id:                 String,
+    pub element_id:                 ElementId,
+    pub release_id:                 ReleaseId,
+    #[serde(deserialize_with = "elements_from_map", serialize_with = "elements_to_map")]
     pub elements:                   Vec<ReleaseKeyVal>,
 }
 
-/// See [Fred docs: /fred/release/related_tags](https://fred.stlouisfed.org/docs/api/fred/release_related_tags.html).
-#[derive(Debug, Deserialize)]
+/// See [Fred docs: /fred/release/tables](https://fred.stlouisfed.org/docs/api/fred/release_tables.html).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseKeyVal {
     pub key:                        isize,
     pub value:                      ReleaseElement,
 }
 
-/// See [Fred docs: /fred/release/related_tags](https://fred.stlouisfed.org/docs/api/fred/release_related_tags.html).
-#[derive(Debug, Deserialize)]
+// FRED returns the elements of a release table as an object keyed by element id.
+fn elements_from_map<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<ReleaseKeyVal>, D::Error> {
+    let elements = std::collections::BTreeMap::<isize, ReleaseElement>::deserialize(deserializer)?;
+    Ok(elements.into_iter().map(|(key, value)| ReleaseKeyVal { key, value }).collect())
+}
+
+fn elements_to_map<S: serde::Serializer>(elements: &[ReleaseKeyVal], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(elements.iter().map(|element| (element.key.to_string(), &element.value)))
+}
+
+/// See [Fred docs: /fred/release/tables](https://fred.stlouisfed.org/docs/api/fred/release_tables.html).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ReleaseElement {
-    pub element_id:                 isize,
-    pub release_id:                 String,
-    pub series_id:                  String,
-    pub parent_id:                  String,
+    pub element_id:                 ElementId,
+    pub release_id:                 ReleaseId,
+    pub series_id:                  Option<SeriesId>,   // none for a section or group
+    pub parent_id:                  Option<ElementId>,
     pub line:                       String,
     #[serde(rename = "type")] 
     pub ty:                         String,
@@ -835,7 +1053,7 @@ pub struct ReleaseElement {
     pub children:                   Vec<ReleaseElement>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Series {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -860,15 +1078,17 @@ impl fmt::Display for Series {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesItem {
-    pub id:                         String,
+    pub id:                         SeriesId,
     pub realtime_start:             String,
     pub realtime_end:               String,
     pub title:                      String,
     pub observation_start:          String,
     pub observation_end:            String,
     pub frequency:                  String,
+    #[serde(default)]
+    pub frequency_short:            Option<String>,
     pub units:                      String,
     pub units_short:                String,
     pub seasonal_adjustment:        String,
@@ -883,7 +1103,7 @@ impl SeriesItem {
     pub fn keytree(&self) -> KeyTreeString {
         let mut s = KeyTreeString::new();
         s.push_key(0, "series_item");
-        s.push_keyvalue(1, "id", &self.id);
+        s.push_keyvalue(1, "id", self.id.as_str());
         s.push_keyvalue(1, "realtime_start", &self.realtime_start);
         s.push_keyvalue(1, "realtime_end", &self.realtime_end);
         s.push_keyvalue(1, "title", &self.title);
@@ -901,6 +1121,337 @@ impl SeriesItem {
     pub fn tags(&self) -> String {
         FredClient::series_tags(&self.id).unwrap().one_line()
     }
+
+    /// The frequency as a `Frequency`. The long form is preferred because only it carries the
+    /// day a weekly series ends on.
+    pub fn frequency_kind(&self) -> Frequency {
+        match (Frequency::from(self.frequency.as_str()), &self.frequency_short) {
+            (Frequency::Other(_), Some(short)) => Frequency::from(short.as_str()),
+            (freq, _) => freq,
+        }
+    }
+
+    /// The units as a `Units`, parsed from `units_short` and falling back to `units`.
+    pub fn units_kind(&self) -> Units {
+        match Units::from(self.units_short.as_str()) {
+            Units::Other(_) => Units::from(self.units.as_str()),
+            units => units,
+        }
+    }
+
+    /// The seasonal adjustment as a `SeasonalAdjustment`, parsed from
+    /// `seasonal_adjustment_short` and falling back to `seasonal_adjustment`.
+    pub fn seasonal_adjustment_kind(&self) -> SeasonalAdjustment {
+        match SeasonalAdjustment::from(self.seasonal_adjustment_short.as_str()) {
+            SeasonalAdjustment::Other(_) => {
+                SeasonalAdjustment::from(self.seasonal_adjustment.as_str())
+            },
+            sa => sa,
+        }
+    }
+}
+
+/// Day of the week, used for weekly frequencies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    fn from_name(s: &str) -> Option<Weekday> {
+        match s.trim().to_lowercase().as_str() {
+            "monday" => Some(Weekday::Monday),
+            "tuesday" => Some(Weekday::Tuesday),
+            "wednesday" => Some(Weekday::Wednesday),
+            "thursday" => Some(Weekday::Thursday),
+            "friday" => Some(Weekday::Friday),
+            "saturday" => Some(Weekday::Saturday),
+            "sunday" => Some(Weekday::Sunday),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Weekday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Weekday::Monday => "Monday",
+            Weekday::Tuesday => "Tuesday",
+            Weekday::Wednesday => "Wednesday",
+            Weekday::Thursday => "Thursday",
+            Weekday::Friday => "Friday",
+            Weekday::Saturday => "Saturday",
+            Weekday::Sunday => "Sunday",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Frequency of a series. Parses the short codes (`"W"`), the request parameter codes (`"wef"`)
+/// and the long form (`"Weekly, Ending Friday"`). Anything else is kept as `Other`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    WeeklyEnding(Weekday),
+    Biweekly,
+    BiweeklyEnding(Weekday),
+    Monthly,
+    Quarterly,
+    Semiannual,
+    Annual,
+    Other(String),
+}
+
+impl Frequency {
+    /// The value of the FRED `frequency` request parameter, or `None` for a biweekly period
+    /// ending on a day FRED has no code for. FRED only has biweekly periods ending Monday or
+    /// Wednesday.
+    pub fn code(&self) -> Option<String> {
+        let code = match self {
+            Frequency::Daily => "d",
+            Frequency::Weekly => "w",
+            Frequency::WeeklyEnding(day) => match day {
+                Weekday::Monday => "wem",
+                Weekday::Tuesday => "wetu",
+                Weekday::Wednesday => "wew",
+                Weekday::Thursday => "weth",
+                Weekday::Friday => "wef",
+                Weekday::Saturday => "wesa",
+                Weekday::Sunday => "wesu",
+            },
+            Frequency::Biweekly => "bw",
+            Frequency::BiweeklyEnding(day) => match day {
+                Weekday::Monday => "bwem",
+                Weekday::Wednesday => "bwew",
+                _ => return None,
+            },
+            Frequency::Monthly => "m",
+            Frequency::Quarterly => "q",
+            Frequency::Semiannual => "sa",
+            Frequency::Annual => "a",
+            Frequency::Other(s) => return Some(s.clone()),
+        };
+        Some(code.to_string())
+    }
+
+    /// The short code FRED uses in `frequency_short`.
+    pub fn short(&self) -> String {
+        match self {
+            Frequency::Daily => "D".into(),
+            Frequency::Weekly | Frequency::WeeklyEnding(_) => "W".into(),
+            Frequency::Biweekly | Frequency::BiweeklyEnding(_) => "BW".into(),
+            Frequency::Monthly => "M".into(),
+            Frequency::Quarterly => "Q".into(),
+            Frequency::Semiannual => "SA".into(),
+            Frequency::Annual => "A".into(),
+            Frequency::Other(s) => s.clone(),
+        }
+    }
+
+    /// True for all weekly variants, ending on a particular day or not.
+    pub fn is_weekly(&self) -> bool {
+        matches!(self, Frequency::Weekly | Frequency::WeeklyEnding(_))
+    }
+
+    /// True for all biweekly variants, ending on a particular day or not.
+    pub fn is_biweekly(&self) -> bool {
+        matches!(self, Frequency::Biweekly | Frequency::BiweeklyEnding(_))
+    }
+
+    /// True if a series at `frequency` has this frequency. `Weekly` and `Biweekly` match any
+    /// ending day.
+    pub fn matches(&self, frequency: &Frequency) -> bool {
+        match self {
+            Frequency::Weekly => frequency.is_weekly(),
+            Frequency::Biweekly => frequency.is_biweekly(),
+            _ => self == frequency,
+        }
+    }
+
+    /// The number of observations per year FRED uses to annualize, with 260 business days a year.
+    pub fn per_year(&self) -> Option<u32> {
+        match self {
+            Frequency::Daily => Some(260),
+            Frequency::Weekly | Frequency::WeeklyEnding(_) => Some(52),
+            Frequency::Biweekly | Frequency::BiweeklyEnding(_) => Some(26),
+            Frequency::Monthly => Some(12),
+            Frequency::Quarterly => Some(4),
+            Frequency::Semiannual => Some(2),
+            Frequency::Annual => Some(1),
+            Frequency::Other(_) => None,
+        }
+    }
+}
+
+impl From<&str> for Frequency {
+    fn from(s: &str) -> Self {
+        let lower = s.trim().to_lowercase();
+
+        // Long form, for example "Weekly, Ending Friday" or "Daily, Close".
+        let mut parts = lower.splitn(2, ',');
+        let head = parts.next().unwrap_or("").trim();
+        let ending = parts
+            .next()
+            .and_then(|tail| tail.trim().strip_prefix("ending"))
+            .and_then(Weekday::from_name);
+
+        match (head, ending) {
+            ("weekly", Some(day)) => return Frequency::WeeklyEnding(day),
+            ("biweekly", Some(day)) => return Frequency::BiweeklyEnding(day),
+            _ => {},
+        }
+
+        match head {
+            "d" | "daily" => Frequency::Daily,
+            "w" | "weekly" => Frequency::Weekly,
+            "bw" | "biweekly" => Frequency::Biweekly,
+            "m" | "monthly" => Frequency::Monthly,
+            "q" | "quarterly" => Frequency::Quarterly,
+            "sa" | "semiannual" => Frequency::Semiannual,
+            "a" | "annual" => Frequency::Annual,
+            "wem" => Frequency::WeeklyEnding(Weekday::Monday),
+            "wetu" => Frequency::WeeklyEnding(Weekday::Tuesday),
+            "wew" => Frequency::WeeklyEnding(Weekday::Wednesday),
+            "weth" => Frequency::WeeklyEnding(Weekday::Thursday),
+            "wef" => Frequency::WeeklyEnding(Weekday::Friday),
+            "wesa" => Frequency::WeeklyEnding(Weekday::Saturday),
+            "wesu" => Frequency::WeeklyEnding(Weekday::Sunday),
+            "bwem" => Frequency::BiweeklyEnding(Weekday::Monday),
+            "bwew" => Frequency::BiweeklyEnding(Weekday::Wednesday),
+            _ => Frequency::Other(s.trim().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frequency::Daily => write!(f, "Daily"),
+            Frequency::Weekly => write!(f, "Weekly"),
+            Frequency::WeeklyEnding(day) => write!(f, "Weekly, Ending {}", day),
+            Frequency::Biweekly => write!(f, "Biweekly"),
+            Frequency::BiweeklyEnding(day) => write!(f, "Biweekly, Ending {}", day),
+            Frequency::Monthly => write!(f, "Monthly"),
+            Frequency::Quarterly => write!(f, "Quarterly"),
+            Frequency::Semiannual => write!(f, "Semiannual"),
+            Frequency::Annual => write!(f, "Annual"),
+            Frequency::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Seasonal adjustment of a series, parsed from the short codes or the long form. Anything else
+/// is kept as `Other`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SeasonalAdjustment {
+    /// Seasonally Adjusted
+    SA,
+    /// Not Seasonally Adjusted
+    NSA,
+    /// Seasonally Adjusted Annual Rate
+    SAAR,
+    /// Smoothed Seasonally Adjusted
+    SSA,
+    /// Not Applicable
+    NA,
+    Other(String),
+}
+
+impl From<&str> for SeasonalAdjustment {
+    fn from(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "sa" | "seasonally adjusted" => SeasonalAdjustment::SA,
+            "nsa" | "not seasonally adjusted" => SeasonalAdjustment::NSA,
+            "saar" | "seasonally adjusted annual rate" => SeasonalAdjustment::SAAR,
+            "ssa" | "smoothed seasonally adjusted" => SeasonalAdjustment::SSA,
+            "na" | "not applicable" => SeasonalAdjustment::NA,
+            _ => SeasonalAdjustment::Other(s.trim().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SeasonalAdjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeasonalAdjustment::SA => write!(f, "SA"),
+            SeasonalAdjustment::NSA => write!(f, "NSA"),
+            SeasonalAdjustment::SAAR => write!(f, "SAAR"),
+            SeasonalAdjustment::SSA => write!(f, "SSA"),
+            SeasonalAdjustment::NA => write!(f, "NA"),
+            SeasonalAdjustment::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Broad classes of units. FRED units are free text, so this only distinguishes the common cases
+/// and keeps anything else as `Other`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Units {
+    Percent,
+    PercentChange,
+    /// A change in the level, such as "Change, Billions of Dollars".
+    Change,
+    Index,
+    Dollars,
+    Persons,
+    Ratio,
+    Rate,
+    Number,
+    Other(String),
+}
+
+impl From<&str> for Units {
+    fn from(s: &str) -> Self {
+        let lower = s.trim().to_lowercase();
+        if lower.contains("percent change")
+            || lower.contains("rate of change")
+            || (lower.starts_with('%') && lower.contains("chg"))
+        {
+            Units::PercentChange
+        } else if lower.starts_with("change") || lower.starts_with("chg") {
+            Units::Change
+        } else if lower.starts_with('%') || lower.starts_with("percent") {
+            Units::Percent
+        } else if lower.starts_with("index") {
+            Units::Index
+        } else if lower.contains('$') || lower.contains("dollars") {
+            Units::Dollars
+        } else if lower.contains("persons") {
+            Units::Persons
+        } else if lower.starts_with("ratio") {
+            Units::Ratio
+        } else if lower.starts_with("rate") {
+            Units::Rate
+        } else if lower.starts_with("number") {
+            Units::Number
+        } else {
+            Units::Other(s.trim().to_string())
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Units::Percent => write!(f, "Percent"),
+            Units::PercentChange => write!(f, "Percent Change"),
+            Units::Change => write!(f, "Change"),
+            Units::Index => write!(f, "Index"),
+            Units::Dollars => write!(f, "Dollars"),
+            Units::Persons => write!(f, "Persons"),
+            Units::Ratio => write!(f, "Ratio"),
+            Units::Rate => write!(f, "Rate"),
+            Units::Number => write!(f, "Number"),
+            Units::Other(s) => write!(f, "{}", s),
+        }
+    }
 }
 
 impl fmt::Display for SeriesItem {
@@ -911,19 +1462,19 @@ impl fmt::Display for SeriesItem {
 }
 
 /// See [Fred docs: /fred/series/categories](https://fred.stlouisfed.org/docs/api/fred/series_categories.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesCategories {
     pub categories:                 Vec<CategoryItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CategoryItem {
-    pub id:                         String,
+    pub id:                         CategoryId,
     pub name:                       String,
-    pub parent_id:                  isize,
+    pub parent_id:                  CategoryId,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Observations(Vec<Observation>);
 
 impl Observations {
@@ -966,7 +1517,7 @@ impl<'a> Iterator for ObservationsIter<'a> {
 }
 
 /// See [Fred docs: /fred/series/observations](https://fred.stlouisfed.org/docs/api/fred/series_observations.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesObservations {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -984,7 +1535,7 @@ pub struct SeriesObservations {
 }
 
 /// See [Fred docs: /fred/series/observations](https://fred.stlouisfed.org/docs/api/fred/series_observations.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Observation {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -999,7 +1550,7 @@ impl fmt::Display for Observation {
 }
 
 /// See [Fred docs: /fred/series/release](https://fred.stlouisfed.org/docs/api/fred/series_release.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesRelease {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -1007,7 +1558,7 @@ pub struct SeriesRelease {
 }
 
 /// See [Fred docs: /fred/series/search](https://fred.stlouisfed.org/docs/api/fred/series_search.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesSearch {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1019,7 +1570,7 @@ pub struct SeriesSearch {
     pub seriess:                    SeriesItems,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesSearchTags {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1032,7 +1583,7 @@ pub struct SeriesSearchTags {
 }
 
 /// See [Fred docs: /fred/series/search/related_tags](https://fred.stlouisfed.org/docs/api/fred/series_search_related_tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesSearchRelatedTags {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1045,7 +1596,7 @@ pub struct SeriesSearchRelatedTags {
 }
 
 /// See [Fred docs: /fred/series/tags](https://fred.stlouisfed.org/docs/api/fred/series_tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesTags {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1061,7 +1612,7 @@ impl SeriesTags {
     pub fn one_line(&self) -> String {
         let mut s = String::new();
         for tag in self.tags.iter() {
-            s.push_str(&tag.name);
+            s.push_str(tag.name.as_str());
             s.push_str(", ");
         };
         s  
@@ -1082,7 +1633,7 @@ impl fmt::Display for SeriesTags {
 }
 
 /// See [Fred docs: /fred/series/updates](https://fred.stlouisfed.org/docs/api/fred/series_updates.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesUpdates {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1097,7 +1648,7 @@ pub struct SeriesUpdates {
 }
 
 /// See [Fred docs: /fred/series/vintage_dates](https://fred.stlouisfed.org/docs/api/fred/series_vintagedates.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SeriesVintageDates {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1109,8 +1660,20 @@ pub struct SeriesVintageDates {
     pub vintage_dates:              Vec<String>,
 }
 
+impl SeriesVintageDates {
+    /// The latest vintage date on or before `date`, or `None` if the series had not been published
+    /// by then.
+    pub fn as_of(&self, date: &str) -> Option<&str> {
+        self.vintage_dates
+            .iter()
+            .map(|d| d.as_str())
+            .filter(|d| *d <= date)
+            .max()
+    }
+}
+
 /// See [Fred docs: /fred/sources](https://fred.stlouisfed.org/docs/api/fred/sources.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Sources {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1123,7 +1686,7 @@ pub struct Sources {
 }
 
 /// See [Fred docs: /fred/source/releases](https://fred.stlouisfed.org/docs/api/fred/source_releases.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SourceReleases {
     pub realtime_start:             String, 
     pub realtime_end:               String, 
@@ -1137,7 +1700,7 @@ pub struct SourceReleases {
 
 
 /// See [Fred docs: /fred/tags](https://fred.stlouisfed.org/docs/api/fred/tags.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Tags {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -1164,7 +1727,7 @@ impl fmt::Display for Tags {
 }
 
 /// See [Fred docs: /fred/tags/series](https://fred.stlouisfed.org/docs/api/fred/tags_series.html).
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct TagsSeries {
     pub realtime_start:             String,
     pub realtime_end:               String,
@@ -1206,12 +1769,136 @@ impl Display for TagsSeries {
 #[cfg(test)]
 mod test {
     use crate::{
+        CategoryId,
         FredClient,
         FredClientIter,
+        FredRequest,
+        Frequency,
+        IntoRequest,
+        ReleaseElement,
+        SeasonalAdjustment,
+        SeriesId,
+        SeriesItem,
+        SeriesItems,
+        Units,
+        Weekday,
     };
 
+    /// Metadata of a monthly series for tests. Set other fields with struct update syntax.
+    pub(crate) fn series_item(id: &str) -> SeriesItem {
+        SeriesItem {
+            id:                         SeriesId::new(id).unwrap(),
+            realtime_start:             "2020-01-01".into(),
+            realtime_end:               "2020-01-01".into(),
+            title:                      format!("Series {}", id),
+            observation_start:          "2020-01-01".into(),
+            observation_end:            "2020-12-01".into(),
+            frequency:                  "Monthly".into(),
+            frequency_short:            None,
+            units:                      "Percent".into(),
+            units_short:                "%".into(),
+            seasonal_adjustment:        "Seasonally Adjusted".into(),
+            seasonal_adjustment_short:  "SA".into(),
+            last_updated:               "2020-01-01 08:00:00-05".into(),
+            popularity:                 1,
+            group_popularity:           None,
+            notes:                      None,
+        }
+    }
+
     #[test]
     fn fred_client_iter_works() {
-        let iter = FredClientIter::new(vec![1, 2, 3].iter(), |id| FredClient::category(*id));
+        let iter = FredClientIter::new(vec![1, 2, 3].iter(), |id| FredClient::category(CategoryId::new(*id)));
+    }
+
+    #[test]
+    fn frequency_parses_short_long_and_parameter_codes() {
+        assert_eq!(Frequency::from("M"), Frequency::Monthly);
+        assert_eq!(Frequency::from("Quarterly"), Frequency::Quarterly);
+        assert_eq!(
+            Frequency::from("Weekly, Ending Friday"),
+            Frequency::WeeklyEnding(Weekday::Friday),
+        );
+        assert_eq!(Frequency::from("wef"), Frequency::WeeklyEnding(Weekday::Friday));
+        assert_eq!(Frequency::from("Daily, Close"), Frequency::Daily);
+        assert_eq!(Frequency::from("Hourly"), Frequency::Other("Hourly".into()));
+        assert_eq!(Frequency::WeeklyEnding(Weekday::Thursday).code().as_deref(), Some("weth"));
+        for day in &[Weekday::Monday, Weekday::Wednesday] {
+            let code = Frequency::BiweeklyEnding(*day).code().unwrap();
+            assert_eq!(Frequency::from(code.as_str()), Frequency::BiweeklyEnding(*day));
+        }
+        assert_eq!(Frequency::BiweeklyEnding(Weekday::Friday).code(), None);
+        assert!(Frequency::Weekly.matches(&Frequency::from("Weekly, Ending Saturday")));
+        assert!(Frequency::Biweekly.matches(&Frequency::from("Biweekly, Ending Monday")));
+        assert!(!Frequency::WeeklyEnding(Weekday::Friday).matches(&Frequency::from("Weekly, Ending Saturday")));
+        assert!(!Frequency::Weekly.matches(&Frequency::Monthly));
+    }
+
+    #[test]
+    fn with_frequency_matches_any_weekly_ending_day() {
+        let weekly = SeriesItem { frequency: "Weekly, Ending Saturday".into(), ..series_item("ICSA") };
+        let items = SeriesItems(vec![weekly, series_item("UNRATE")]);
+        let ids = |items: SeriesItems| items.iter().map(|s| s.id.to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(items.with_frequency(&Frequency::Weekly)), vec!["ICSA"]);
+        assert_eq!(ids(items.with_frequency(&Frequency::WeeklyEnding(Weekday::Saturday))), vec!["ICSA"]);
+        assert_eq!(ids(items.with_frequency(&Frequency::Monthly)), vec!["UNRATE"]);
+    }
+
+    #[test]
+    fn seasonal_adjustment_and_units_parse() {
+        assert_eq!(SeasonalAdjustment::from("SAAR"), SeasonalAdjustment::SAAR);
+        assert_eq!(SeasonalAdjustment::from("Not Seasonally Adjusted"), SeasonalAdjustment::NSA);
+        assert_eq!(SeasonalAdjustment::from("XYZ"), SeasonalAdjustment::Other("XYZ".into()));
+        assert_eq!(Units::from("% Chg. from Yr. Ago"), Units::PercentChange);
+        assert_eq!(Units::from("Percent Change from Year Ago"), Units::PercentChange);
+        assert_eq!(Units::from("Change, Billions of Dollars"), Units::Change);
+        assert_eq!(Units::from("Chg. in Bil. of $"), Units::Change);
+        assert_eq!(Units::from("Index 2015=100"), Units::Index);
+        assert_eq!(Units::from("Bil. of $"), Units::Dollars);
+    }
+
+    #[test]
+    fn release_element_has_typed_ids() {
+        let section: ReleaseElement = serde_json::from_str(r#"{
+            "element_id": 12886, "release_id": "53", "series_id": null, "parent_id": null,
+            "line": "1", "type": "section", "name": "Gross domestic product", "level": "0",
+            "children": [{
+                "element_id": 12887, "release_id": "53", "series_id": "DGDSRL1A225NBEA",
+                "parent_id": 12886, "line": "2", "type": "series", "name": "Goods", "level": "1",
+                "children": []
+            }]
+        }"#).unwrap();
+        let goods = &section.children[0];
+        assert_eq!(section.series_id, None);
+        assert_eq!(goods.series_id, Some(SeriesId::new("DGDSRL1A225NBEA").unwrap()));
+        assert_eq!(goods.parent_id, Some(section.element_id));
+    }
+
+    #[test]
+    fn raw_request_includes_arbitrary_params() {
+        std::env::set_var("FRED_API_KEY", "abcdef");
+        let req = FredRequest::from_pairs(
+            "series/observations",
+            &[("series_id".to_string(), "GNPCA"), ("units".to_string(), "pch")],
+        );
+        assert_eq!(
+            req.into_request().unwrap(),
+            "https://api.stlouisfed.org/fred/series/observations?series_id=GNPCA&units=pch\
+                &api_key=abcdef&file_type=json",
+        );
+    }
+
+    #[test]
+    fn request_values_are_percent_encoded() {
+        std::env::set_var("FRED_API_KEY", "abcdef");
+        let req = FredRequest::from_pairs(
+            "tags/series",
+            &[("tag_names", "price index;usa"), ("search_text", "a&b=c")],
+        );
+        assert_eq!(
+            req.into_request().unwrap(),
+            "https://api.stlouisfed.org/fred/tags/series?tag_names=price%20index%3Busa\
+                &search_text=a%26b%3Dc&api_key=abcdef&file_type=json",
+        );
     }
 }
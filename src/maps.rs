@@ -0,0 +1,320 @@
+//! An API to the [FRED Maps API](https://fred.stlouisfed.org/docs/api/geofred/), which serves
+//! regional cross-sections of series groups, such as per capita personal income for every state.
+//!
+//! ```
+//! let group = MapsClient::series_group(&SeriesId::new("SMU56000000500000001SA")?)?;
+//! let query = RegionalDataQuery::new("882", RegionType::State, "2013-01-01")
+//!     .season(SeasonalAdjustment::NSA)
+//!     .units("Dollars")
+//!     .frequency(Frequency::Annual);
+//! let data = MapsClient::regional_data(&query)?;
+//! ```
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+use crate::{
+    req,
+    FredRequest,
+    Frequency,
+    IntoRequest,
+    SeasonalAdjustment,
+    SeriesId,
+};
+
+mod geojson;
+
+pub use geojson::{Feature, FeatureCollection, RegionProperties, ShapeCache};
+
+/// A request to the Maps API. Shares its parameters, API key and response handling with
+/// `FredRequest` but lives under `geofred/` rather than `fred/`.
+#[derive(Debug)]
+pub struct MapsRequest(FredRequest);
+
+impl MapsRequest {
+    pub fn new<T: Display>(url: &str, keyvals: Vec<(&'static str, T)>) -> Result<Self> {
+        Ok(MapsRequest(FredRequest::new(url, keyvals)?))
+    }
+}
+
+impl IntoRequest for MapsRequest {
+    fn into_request(&self) -> Result<String> {
+        Ok(
+            format!(
+                "{}geofred/{}?{}&api_key={}&{}",
+                self.base_url()?,
+                self.0.url,
+                self.0.concat_keyvals('&'),
+                self.api_key()?,
+                self.0.format,
+            )
+        )
+    }
+
+    fn base_url(&self) -> Result<String> {
+        self.0.base_url()
+    }
+
+    fn api_key(&self) -> Result<String> {
+        self.0.api_key()
+    }
+}
+
+/// Encapsulate the Maps API requests.
+pub struct MapsClient;
+
+impl MapsClient {
+    /// [Get the meta information for a series group.](https://fred.stlouisfed.org/docs/api/geofred/series_group.html)
+    pub fn series_group(series_id: &SeriesId) -> Result<SeriesGroup> {
+        req(MapsRequest::new("series/group", vec![("series_id", series_id)])?)
+    }
+
+    /// [Get a cross-section of the series group that `series_id` belongs to.](https://fred.stlouisfed.org/docs/api/geofred/series_data.html)
+    /// With no `date` the most recent cross-section is returned. With `start_date` every
+    /// cross-section from that date is returned.
+    pub fn series_data(series_id: &SeriesId, date: Option<&str>, start_date: Option<&str>) -> Result<RegionalData> {
+        let mut keyvals = vec![("series_id", series_id.to_string())];
+        if let Some(date) = date {
+            keyvals.push(("date", date.to_string()));
+        }
+        if let Some(start_date) = start_date {
+            keyvals.push(("start_date", start_date.to_string()));
+        }
+        req(MapsRequest::new("series/data", keyvals)?)
+    }
+
+    /// [Get a cross-section of a series group for a region type.](https://fred.stlouisfed.org/docs/api/geofred/regional_data.html)
+    pub fn regional_data(query: &RegionalDataQuery) -> Result<RegionalData> {
+        req(MapsRequest::new("regional/data", query.keyvals()?)?)
+    }
+
+    /// [Get the shape file for a region type.](https://fred.stlouisfed.org/docs/api/geofred/shapes.html)
+    pub fn shape_file(shape: RegionType) -> Result<ShapeFile> {
+        req(MapsRequest::new("shapes/file", vec![("shape", shape)])?)
+    }
+}
+
+/// The region types of the Maps API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RegionType {
+    Bea,
+    Msa,
+    Frb,
+    Necta,
+    State,
+    Country,
+    County,
+    CensusRegion,
+    CensusDivision,
+}
+
+impl Display for RegionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RegionType::Bea => "bea",
+            RegionType::Msa => "msa",
+            RegionType::Frb => "frb",
+            RegionType::Necta => "necta",
+            RegionType::State => "state",
+            RegionType::Country => "country",
+            RegionType::County => "county",
+            RegionType::CensusRegion => "censusregion",
+            RegionType::CensusDivision => "censusdivision",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Parameters of a `regional/data` request. The series group, region type and date are required;
+/// the rest default to the values FRED uses most often.
+#[derive(Clone, Debug)]
+pub struct RegionalDataQuery {
+    series_group:       String,
+    region_type:        RegionType,
+    date:               String,
+    start_date:         Option<String>,
+    season:             SeasonalAdjustment,
+    units:              String,
+    frequency:          Frequency,
+    transformation:     String,
+    aggregation_method: Option<String>,
+}
+
+impl RegionalDataQuery {
+    pub fn new(series_group: &str, region_type: RegionType, date: &str) -> Self {
+        RegionalDataQuery {
+            series_group:       series_group.to_string(),
+            region_type,
+            date:               date.to_string(),
+            start_date:         None,
+            season:             SeasonalAdjustment::NSA,
+            units:              "Dollars".to_string(),
+            frequency:          Frequency::Annual,
+            transformation:     "lin".to_string(),
+            aggregation_method: None,
+        }
+    }
+
+    pub fn start_date(mut self, start_date: &str) -> Self {
+        self.start_date = Some(start_date.to_string());
+        self
+    }
+
+    pub fn season(mut self, season: SeasonalAdjustment) -> Self {
+        self.season = season;
+        self
+    }
+
+    pub fn units(mut self, units: &str) -> Self {
+        self.units = units.to_string();
+        self
+    }
+
+    pub fn frequency(mut self, frequency: Frequency) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// One of `lin`, `chg`, `ch1`, `pch`, `pc1`, `pca`, `cch`, `cca` or `log`.
+    pub fn transformation(mut self, transformation: &str) -> Self {
+        self.transformation = transformation.to_string();
+        self
+    }
+
+    /// One of `avg`, `sum` or `eop`.
+    pub fn aggregation_method(mut self, aggregation_method: &str) -> Self {
+        self.aggregation_method = Some(aggregation_method.to_string());
+        self
+    }
+
+    fn keyvals(&self) -> Result<Vec<(&'static str, String)>> {
+        let frequency = self
+            .frequency
+            .code()
+            .ok_or_else(|| anyhow!("FRED has no frequency code for [{}].", self.frequency))?;
+        let mut keyvals = vec![
+            ("series_group", self.series_group.clone()),
+            ("region_type", self.region_type.to_string()),
+            ("date", self.date.clone()),
+            ("season", self.season.to_string()),
+            ("units", self.units.clone()),
+            ("frequency", frequency),
+            ("transformation", self.transformation.clone()),
+        ];
+        if let Some(start_date) = &self.start_date {
+            keyvals.push(("start_date", start_date.clone()));
+        }
+        if let Some(aggregation_method) = &self.aggregation_method {
+            keyvals.push(("aggregation_method", aggregation_method.clone()));
+        }
+        Ok(keyvals)
+    }
+}
+
+// Response data-structures ///////////////////////////////////////////////////////////////////////
+
+/// See [Fred docs: /geofred/series/group](https://fred.stlouisfed.org/docs/api/geofred/series_group.html).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SeriesGroup {
+    pub series_group:               SeriesGroupItem,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SeriesGroupItem {
+    pub title:                      String,
+    pub region_type:                String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub series_group:               String,
+    pub season:                     String,
+    pub units:                      String,
+    pub frequency:                  String,
+    pub min_date:                   String,
+    pub max_date:                   String,
+}
+
+/// See [Fred docs: /geofred/regional/data](https://fred.stlouisfed.org/docs/api/geofred/regional_data.html).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RegionalData {
+    pub meta:                       RegionalMeta,
+}
+
+impl RegionalData {
+    /// The dates of the cross-sections, in order.
+    pub fn dates(&self) -> Vec<&str> {
+        self.meta.data.keys().map(|date| date.as_str()).collect()
+    }
+
+    /// The cross-section for `date`.
+    pub fn cross_section(&self, date: &str) -> Option<&[RegionalValue]> {
+        self.meta.data.get(date).map(|values| values.as_slice())
+    }
+
+    /// The most recent cross-section.
+    pub fn latest(&self) -> Option<(&str, &[RegionalValue])> {
+        self.meta.data.iter().next_back().map(|(date, values)| (date.as_str(), values.as_slice()))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RegionalMeta {
+    pub title:                      String,
+    pub region:                     String,
+    pub seasonality:                String,
+    pub units:                      String,
+    pub frequency:                  String,
+    /// Cross-sections keyed by date.
+    pub data:                       BTreeMap<String, Vec<RegionalValue>>,
+}
+
+/// The value of a single region in a cross-section.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RegionalValue {
+    pub region:                     String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub code:                       String,
+    pub value:                      Option<f64>,
+    pub series_id:                  SeriesId,
+}
+
+/// See [Fred docs: /geofred/shapes/file](https://fred.stlouisfed.org/docs/api/geofred/shapes.html).
+/// The shapes are kept as returned.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ShapeFile(pub serde_json::Value);
+
+// Region codes and series groups are strings such as "01", but sometimes arrive as numbers.
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!("Expected string or number, found [{}].", other))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        IntoRequest,
+        Frequency,
+        SeasonalAdjustment,
+        maps::{MapsRequest, RegionalDataQuery, RegionType},
+    };
+
+    #[test]
+    fn regional_data_request_uses_geofred_path() {
+        std::env::set_var("FRED_API_KEY", "abcdef");
+        let query = RegionalDataQuery::new("882", RegionType::State, "2013-01-01")
+            .season(SeasonalAdjustment::NSA)
+            .frequency(Frequency::Annual);
+        let req = MapsRequest::new("regional/data", query.keyvals().unwrap()).unwrap();
+        assert_eq!(
+            req.into_request().unwrap(),
+            "https://api.stlouisfed.org/geofred/regional/data?series_group=882&region_type=state\
+                &date=2013-01-01&season=NSA&units=Dollars&frequency=a&transformation=lin\
+                &api_key=abcdef&file_type=json",
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! GeoJSON output for regional data. Each region of a cross-section is joined to its shape, so the
+//! result can be given directly to a mapping front end.
+//!
+//! ```
+//! let cache = ShapeCache::new("shapes");
+//! let shapes = cache.get(RegionType::State)?;
+//! let data = MapsClient::regional_data(&query)?;
+//! let collection = FeatureCollection::join(&data, &shapes, "2013-01-01")?;
+//! std::fs::write("income.geojson", collection.to_json()?)?;
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use crate::{
+    SeriesId,
+    maps::{MapsClient, RegionalData, RegionalValue, RegionType, ShapeFile},
+};
+
+// Feature properties and ids that may hold a region code, in the order they are tried.
+const CODE_KEYS: [&str; 5] = ["fips", "code", "GEOID", "geoid", "id"];
+
+impl ShapeFile {
+    /// The features of the shape file. Empty if it is not a feature collection.
+    pub fn features(&self) -> &[Value] {
+        self.0
+            .get("features")
+            .and_then(|features| features.as_array())
+            .map(|features| features.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ShapeFile> {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path).context(format!("Failed to read [{}]", path.display()))?;
+        serde_json::from_str(&s).context(format!("Failed to parse [{}]", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_string(self)?)
+            .context(format!("Failed to write [{}]", path.display()))
+    }
+}
+
+/// Shape files cached in a directory, one `<region_type>.json` per region type. Shape files rarely
+/// change, so they are only fetched when missing or on `refresh()`.
+#[derive(Clone, Debug)]
+pub struct ShapeCache {
+    dir: PathBuf,
+}
+
+impl ShapeCache {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        ShapeCache { dir: dir.as_ref().to_path_buf() }
+    }
+
+    /// The path of the cached shape file for `shape`.
+    pub fn path(&self, shape: RegionType) -> PathBuf {
+        self.dir.join(format!("{}.json", shape))
+    }
+
+    /// The shape file for `shape`, from the cache or fetched and cached.
+    pub fn get(&self, shape: RegionType) -> Result<ShapeFile> {
+        let path = self.path(shape);
+        if path.exists() {
+            ShapeFile::load(&path)
+        } else {
+            self.refresh(shape)
+        }
+    }
+
+    /// Fetch the shape file for `shape` and replace the cached copy.
+    pub fn refresh(&self, shape: RegionType) -> Result<ShapeFile> {
+        let shape_file = MapsClient::shape_file(shape)?;
+        fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create [{}]", self.dir.display()))?;
+        shape_file.save(self.path(shape))?;
+        Ok(shape_file)
+    }
+}
+
+/// A GeoJSON FeatureCollection with one feature per region.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FeatureCollection {
+    #[serde(rename = "type")]
+    pub ty:                         String,
+    pub features:                   Vec<Feature>,
+    /// Other top-level members of the shape file, such as `crs` and `hc-transform`, which are
+    /// needed to project the shapes.
+    #[serde(flatten)]
+    pub members:                    Map<String, Value>,
+}
+
+/// A GeoJSON Feature carrying the region and its value.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Feature {
+    #[serde(rename = "type")]
+    pub ty:                         String,
+    pub geometry:                   Value,
+    pub properties:                 RegionProperties,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RegionProperties {
+    pub code:                       Option<String>,
+    pub name:                       Option<String>,
+    pub date:                       String,
+    /// `None` when the region has no value in the cross-section.
+    pub value:                      Option<f64>,
+    pub series_id:                  Option<SeriesId>,
+}
+
+impl FeatureCollection {
+    /// Join the cross-section for `date` to the shapes. Regions are matched on their code and
+    /// then on their name. Shapes without a matching region are kept with no value.
+    pub fn join(data: &RegionalData, shapes: &ShapeFile, date: &str) -> Result<FeatureCollection> {
+        let cross_section = data
+            .cross_section(date)
+            .ok_or_else(|| anyhow!("Regional data has no cross-section for [{}].", date))?;
+
+        let by_code: HashMap<&str, &RegionalValue> = cross_section
+            .iter()
+            .map(|value| (value.code.as_str(), value))
+            .collect();
+        let by_name: HashMap<String, &RegionalValue> = cross_section
+            .iter()
+            .map(|value| (value.region.to_lowercase(), value))
+            .collect();
+
+        let mut features = Vec::new();
+        for shape in shapes.features() {
+            let properties = shape.get("properties");
+            let name = properties
+                .and_then(|p| p.get("name"))
+                .and_then(|name| name.as_str());
+
+            let region = CODE_KEYS
+                .iter()
+                .filter_map(|key| properties.and_then(|p| p.get(*key)).or_else(|| shape.get(*key)))
+                .filter_map(value_to_string)
+                .find_map(|code| by_code.get(code.as_str()).copied())
+                .or_else(|| name.and_then(|name| by_name.get(&name.to_lowercase()).copied()));
+
+            features.push(
+                Feature {
+                    ty:             "Feature".into(),
+                    geometry:       shape.get("geometry").cloned().unwrap_or(Value::Null),
+                    properties:     RegionProperties {
+                        code:       region.map(|r| r.code.clone()),
+                        name:       region.map(|r| r.region.clone()).or_else(|| name.map(String::from)),
+                        date:       date.to_string(),
+                        value:      region.and_then(|r| r.value),
+                        series_id:  region.map(|r| r.series_id.clone()),
+                    },
+                }
+            );
+        }
+        let members = shapes.0
+            .as_object()
+            .map(|object| {
+                object
+                    .iter()
+                    .filter(|(key, _)| *key != "type" && *key != "features")
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(FeatureCollection { ty: "FeatureCollection".into(), features, members })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maps::{FeatureCollection, RegionalData, RegionType, ShapeCache, ShapeFile};
+
+    const DATA: &str = r#"{"meta": {
+        "title": "Per Capita Personal Income by State (Dollars)",
+        "region": "state",
+        "seasonality": "Not Seasonally Adjusted",
+        "units": "Dollars",
+        "frequency": "Annual",
+        "data": {"2013-01-01": [
+            {"region": "Alabama", "code": "01", "value": 36481, "series_id": "ALPCPI"},
+            {"region": "Alaska", "code": "02", "value": 50150, "series_id": "AKPCPI"}
+        ]}
+    }}"#;
+
+    const SHAPES: &str = r#"{"type": "FeatureCollection",
+        "crs": {"type": "name", "properties": {"name": "urn:ogc:def:crs:EPSG::102004"}},
+        "hc-transform": {"default": {"scale": 0.00017, "xoffset": -2361383.0}},
+        "features": [
+        {"type": "Feature", "properties": {"name": "Alabama", "fips": "01"}, "geometry": {"type": "Point", "coordinates": [0, 0]}},
+        {"type": "Feature", "properties": {"name": "Alaska"}, "geometry": {"type": "Point", "coordinates": [1, 1]}},
+        {"type": "Feature", "properties": {"name": "Guam"}, "geometry": {"type": "Point", "coordinates": [2, 2]}}
+    ]}"#;
+
+    #[test]
+    fn regions_join_on_code_then_name() {
+        let data: RegionalData = serde_json::from_str(DATA).unwrap();
+        let shapes: ShapeFile = serde_json::from_str(SHAPES).unwrap();
+        let collection = FeatureCollection::join(&data, &shapes, "2013-01-01").unwrap();
+
+        let props: Vec<_> = collection.features.iter().map(|f| &f.properties).collect();
+        assert_eq!(props[0].value, Some(36481.0));
+        assert_eq!(props[1].code.as_deref(), Some("02"));
+        assert_eq!(props[2].name.as_deref(), Some("Guam"));
+        assert_eq!(props[2].value, None);
+        assert!(FeatureCollection::join(&data, &shapes, "2014-01-01").is_err());
+
+        let json: serde_json::Value = serde_json::from_str(&collection.to_json().unwrap()).unwrap();
+        assert_eq!(json["crs"], shapes.0["crs"]);
+        assert_eq!(json["hc-transform"], shapes.0["hc-transform"]);
+        assert_eq!(json["type"], "FeatureCollection");
+    }
+
+    #[test]
+    fn shape_cache_reads_cached_file() {
+        let dir = std::env::temp_dir().join(format!("fred_api_shapes_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache = ShapeCache::new(&dir);
+        let shapes: ShapeFile = serde_json::from_str(SHAPES).unwrap();
+        shapes.save(cache.path(RegionType::State)).unwrap();
+
+        assert_eq!(cache.get(RegionType::State).unwrap(), shapes);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,409 @@
+//! A local mirror of series observations, one JSON file per series. A sync only refetches series
+//! whose `last_updated` has changed. For those it asks for the observations new or revised in
+//! vintages since the stored copy, and fetches only the new tail unless a stored observation was
+//! revised.
+//!
+//! ```
+//! let mirror = Mirror::new("observations");
+//! let ids = vec![SeriesId::new("GDP")?, SeriesId::new("UNRATE")?];
+//! let report = mirror.sync(&ids);
+//! println!("{}", report);
+//! let gdp = mirror.get(&ids[0])?;
+//!
+//! // Many series are cheaper to sync from a refreshed catalog, without a metadata request each.
+//! let mut catalog = Catalog::load("catalog")?;
+//! catalog.refresh()?;
+//! let report = mirror.sync_catalog(&catalog);
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs,
+    path::{Path, PathBuf},
+};
+use crate::{
+    req,
+    FredClient,
+    FredRequest,
+    Observation,
+    Catalog,
+    SeriesId,
+    SeriesItem,
+    SeriesObservations,
+    VintageMatrix,
+    VintageOutput,
+};
+
+/// The stored copy of a series.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MirroredSeries {
+    pub item:                       SeriesItem,
+    pub observations:               Vec<Observation>,
+}
+
+/// What a sync did to each series.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SyncReport {
+    /// Series fetched for the first time.
+    pub added:                      Vec<SeriesId>,
+    /// Series with new observations appended, and the number appended.
+    pub appended:                   Vec<(SeriesId, usize)>,
+    /// Series refetched in full because stored observations were revised.
+    pub revised:                    Vec<SeriesId>,
+    /// Series with the same `last_updated` as the stored copy.
+    pub unchanged:                  Vec<SeriesId>,
+    pub failed:                     Vec<(SeriesId, String)>,
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "added: {}", self.added.len())?;
+        writeln!(f, "appended: {}", self.appended.len())?;
+        writeln!(f, "revised: {}", self.revised.len())?;
+        writeln!(f, "unchanged: {}", self.unchanged.len())?;
+        writeln!(f, "failed: {}", self.failed.len())?;
+        for (id, err) in &self.failed {
+            writeln!(f, "  {}: {}", id, err)?;
+        }
+        Ok(())
+    }
+}
+
+// The outcome of syncing one series.
+enum Synced {
+    Added,
+    Appended(usize),
+    Revised,
+    Unchanged,
+}
+
+type FetchItem = Box<dyn Fn(&SeriesId) -> Result<SeriesItem>>;
+type FetchObservations = Box<dyn Fn(&SeriesId, Option<&str>) -> Result<Vec<Observation>>>;
+type FetchChanges = Box<dyn Fn(&SeriesId, &str) -> Result<VintageMatrix>>;
+
+/// Observations stored in a directory as `<series_id>.json`.
+pub struct Mirror {
+    dir:                PathBuf,
+    fetch_item:         FetchItem,
+    fetch_observations: FetchObservations,
+    fetch_changes:      FetchChanges,
+}
+
+impl Mirror {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Mirror {
+            dir:                dir.as_ref().to_path_buf(),
+            fetch_item:         Box::new(fetch_item),
+            fetch_observations: Box::new(fetch_observations),
+            fetch_changes:      Box::new(fetch_changes),
+        }
+    }
+
+    /// The path of the stored copy of `series_id`.
+    pub fn path(&self, series_id: &SeriesId) -> PathBuf {
+        self.dir.join(format!("{}.json", series_id))
+    }
+
+    /// The stored copy of `series_id`, or `None` if it has not been mirrored.
+    pub fn get(&self, series_id: &SeriesId) -> Result<Option<MirroredSeries>> {
+        let path = self.path(series_id);
+        if !path.exists() {
+            return Ok(None)
+        }
+        let s = fs::read_to_string(&path).context(format!("Failed to read [{}]", path.display()))?;
+        let series = serde_json::from_str(&s).context(format!("Failed to parse [{}]", path.display()))?;
+        Ok(Some(series))
+    }
+
+    fn put(&self, series: &MirroredSeries) -> Result<()> {
+        fs::create_dir_all(&self.dir).context(format!("Failed to create [{}]", self.dir.display()))?;
+        let path = self.path(&series.item.id);
+        fs::write(&path, serde_json::to_string(series)?)
+            .context(format!("Failed to write [{}]", path.display()))
+    }
+
+    /// Bring each series in `series_ids` up to date, fetching the metadata of each. A failure is
+    /// recorded in the report and does not stop the sync.
+    pub fn sync(&self, series_ids: &[SeriesId]) -> SyncReport {
+        self.sync_items(series_ids.iter().map(|id| (id.clone(), (self.fetch_item)(id))))
+    }
+
+    /// Bring each series in `catalog` up to date, using the metadata in the catalog. Refresh the
+    /// catalog first so that it holds the latest `last_updated` of each series.
+    pub fn sync_catalog(&self, catalog: &Catalog) -> SyncReport {
+        self.sync_items(catalog.series.iter().map(|(id, item)| (id.clone(), Ok(item.clone()))))
+    }
+
+    fn sync_items<I>(&self, items: I) -> SyncReport
+    where
+        I: Iterator<Item = (SeriesId, Result<SeriesItem>)>,
+    {
+        let mut report = SyncReport::default();
+        for (id, item) in items {
+            match item.and_then(|item| self.sync_series(item)) {
+                Ok(Synced::Added) => report.added.push(id),
+                Ok(Synced::Appended(n)) => report.appended.push((id, n)),
+                Ok(Synced::Revised) => report.revised.push(id),
+                Ok(Synced::Unchanged) => report.unchanged.push(id),
+                Err(err) => report.failed.push((id, format!("{:#}", err))),
+            }
+        }
+        report
+    }
+
+    fn sync_series(&self, item: SeriesItem) -> Result<Synced> {
+        let series_id = &item.id;
+        let stored = match self.get(series_id)? {
+            Some(stored) => stored,
+            None => {
+                let observations = (self.fetch_observations)(series_id, None)?;
+                self.put(&MirroredSeries { item, observations })?;
+                return Ok(Synced::Added)
+            },
+        };
+        if stored.item.last_updated == item.last_updated {
+            return Ok(Synced::Unchanged)
+        }
+        // A shifted start revised the history.
+        if stored.item.observation_start != item.observation_start {
+            return self.refetch(item)
+        }
+        // Vintages are dated by day, so changes made on the day of the stored copy cannot be told
+        // apart from it.
+        let since = match (vintage_day(&stored.item.last_updated), vintage_day(&item.last_updated)) {
+            (Some(stored_day), Some(day)) if day > stored_day => stored_day + Duration::days(1),
+            _ => return self.refetch(item),
+        };
+        let stored_end = match stored.observations.last() {
+            Some(obs) => obs.date.clone(),
+            None => return self.refetch(item),
+        };
+
+        let changes = (self.fetch_changes)(series_id, &since.format("%Y-%m-%d").to_string())?;
+        // Any change to a stored date, however old, is a revision, such as an annual revision of
+        // GDP released with a new quarter.
+        let revised = changes
+            .dates()
+            .iter()
+            .any(|date| *date <= stored_end && changes.row(date).is_some_and(|row| !row.is_empty()));
+        if revised {
+            return self.refetch(item)
+        }
+
+        let mut observations = stored.observations;
+        let tail_start = changes.dates().iter().filter(|date| **date > stored_end).min();
+        let appended = match tail_start {
+            Some(tail_start) => {
+                let tail = (self.fetch_observations)(series_id, Some(tail_start))?;
+                let tail: Vec<_> = tail.into_iter().filter(|obs| obs.date > stored_end).collect();
+                let appended = tail.len();
+                observations.extend(tail);
+                appended
+            },
+            None => 0,
+        };
+        self.put(&MirroredSeries { item, observations })?;
+        Ok(Synced::Appended(appended))
+    }
+
+    fn refetch(&self, item: SeriesItem) -> Result<Synced> {
+        let observations = (self.fetch_observations)(&item.id, None)?;
+        self.put(&MirroredSeries { item, observations })?;
+        Ok(Synced::Revised)
+    }
+}
+
+pub(crate) fn fetch_item(series_id: &SeriesId) -> Result<SeriesItem> {
+    FredClient::series(series_id)?
+        .seriess
+        .0
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Series [{}] was not found.", series_id))
+}
+
+// Observations new or revised in vintages from `realtime_start` on.
+fn fetch_changes(series_id: &SeriesId, realtime_start: &str) -> Result<VintageMatrix> {
+    let changes = FredClient::series_observations_by_vintage(
+        series_id,
+        VintageOutput::NewAndRevised,
+        realtime_start,
+        "9999-12-31",
+    )?;
+    Ok(changes.observations)
+}
+
+// The day of the vintage published at `last_updated`, such as `2013-07-31 09:26:16-05`.
+fn vintage_day(last_updated: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(last_updated.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn fetch_observations(series_id: &SeriesId, observation_start: Option<&str>) -> Result<Vec<Observation>> {
+    let mut keyvals = vec![("series_id", series_id.to_string())];
+    if let Some(date) = observation_start {
+        keyvals.push(("observation_start", date.to_string()));
+    }
+    let observations: SeriesObservations = req(FredRequest::new("series/observations", keyvals)?)?;
+    Ok(observations.observations.0)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Map, Value};
+    use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+    use crate::{Catalog, CategoryId, Mirror, Observation, SeriesId, SeriesItem};
+
+    fn observation(month: usize, value: &str) -> Observation {
+        Observation {
+            realtime_start: "2021-01-01".into(),
+            realtime_end:   "2021-01-01".into(),
+            date:           format!("2020-{:02}-01", month),
+            value:          value.into(),
+        }
+    }
+
+    // A fake server holding `last_updated`, the full history, each `observation_start` requested,
+    // and each change to the history as `(vintage_date, date, value)`.
+    #[derive(Default)]
+    struct FakeServer {
+        last_updated:   String,
+        history:        Vec<Observation>,
+        starts:         Vec<Option<String>>,
+        changes:        Vec<(String, String, String)>,
+    }
+
+    type Server = Rc<RefCell<FakeServer>>;
+
+    fn server(history: Vec<Observation>) -> Server {
+        Rc::new(RefCell::new(FakeServer { last_updated: "2021-01-01 08:00:00-05".into(), history, ..FakeServer::default() }))
+    }
+
+    fn series_item(server: &Server) -> SeriesItem {
+        let s = server.borrow();
+        SeriesItem {
+            last_updated:               s.last_updated.clone(),
+            observation_end:            s.history.last().unwrap().date.clone(),
+            ..crate::test::series_item("AAA")
+        }
+    }
+
+    fn fake_mirror(dir: &std::path::Path, server: &Server) -> Mirror {
+        let mut mirror = Mirror::new(dir);
+        let s = server.clone();
+        mirror.fetch_item = Box::new(move |_| Ok(series_item(&s)));
+        let s = server.clone();
+        mirror.fetch_observations = Box::new(move |_, start| {
+            let mut s = s.borrow_mut();
+            s.starts.push(start.map(String::from));
+            Ok(s.history.iter().filter(|o| start.is_none_or(|d| o.date.as_str() >= d)).cloned().collect())
+        });
+        let s = server.clone();
+        mirror.fetch_changes = Box::new(move |id, realtime_start| {
+            let mut rows: BTreeMap<&str, Map<String, Value>> = BTreeMap::new();
+            let s = s.borrow();
+            for (vintage, date, value) in s.changes.iter().filter(|(vintage, ..)| vintage.as_str() >= realtime_start) {
+                let row = rows.entry(date).or_default();
+                row.insert("date".into(), json!(date));
+                row.insert(format!("{}_{}", id, vintage.replace('-', "")), json!(value));
+            }
+            Ok(serde_json::from_value(json!(rows.into_values().collect::<Vec<_>>()))?)
+        });
+        mirror
+    }
+
+    // Publish a new vintage at `last_updated`, recording the observations `f` adds or revises.
+    fn update(server: &Server, last_updated: &str, f: impl FnOnce(&mut Vec<Observation>)) {
+        let mut s = server.borrow_mut();
+        let previous = s.history.clone();
+        s.last_updated = last_updated.into();
+        f(&mut s.history);
+        let vintage = &last_updated[..10];
+        let changes: Vec<_> = s
+            .history
+            .iter()
+            .filter(|obs| !previous.contains(obs))
+            .map(|obs| (vintage.to_string(), obs.date.clone(), obs.value.clone()))
+            .collect();
+        s.changes.extend(changes);
+    }
+
+    #[test]
+    fn sync_fetches_tail_and_detects_revisions() {
+        let dir = std::env::temp_dir().join(format!("fred_api_mirror_{}", std::process::id()));
+        let id = SeriesId::new("AAA").unwrap();
+        let ids = [id.clone()];
+        let server = server((1..=4).map(|m| observation(m, &m.to_string())).collect());
+        let mirror = fake_mirror(&dir, &server);
+        let starts = || server.borrow().starts.clone();
+
+        assert_eq!(mirror.sync(&ids).added, vec![id.clone()]);
+        assert_eq!(mirror.sync(&ids).unchanged, vec![id.clone()]);
+
+        // A new observation is appended from the tail.
+        update(&server, "2021-02-01 08:00:00-05", |h| h.push(observation(5, "5")));
+        assert_eq!(mirror.sync(&ids).appended, vec![(id.clone(), 1)]);
+        assert_eq!(starts().last().unwrap().as_deref(), Some("2020-05-01"));
+        assert_eq!(mirror.get(&id).unwrap().unwrap().observations.len(), 5);
+
+        // A revised observation, with a new one, triggers a full refetch.
+        update(&server, "2021-03-01 08:00:00-05", |h| {
+            h[3].value = "4.5".into();
+            h.push(observation(6, "6"));
+        });
+        assert_eq!(mirror.sync(&ids).revised, vec![id.clone()]);
+        assert_eq!(starts().last().unwrap(), &None);
+        assert_eq!(mirror.get(&id).unwrap().unwrap().observations[3].value, "4.5");
+
+        // So does a revision of the oldest observation released with a new one, as with the annual
+        // revision of GDP.
+        update(&server, "2021-04-01 08:00:00-05", |h| {
+            h[0].value = "0.5".into();
+            h.push(observation(7, "7"));
+        });
+        assert_eq!(mirror.sync(&ids).revised, vec![id.clone()]);
+        let stored = mirror.get(&id).unwrap().unwrap();
+        assert_eq!(stored.observations[0].value, "0.5");
+        assert_eq!(stored.observations.len(), 7);
+
+        // And a revision without new observations.
+        update(&server, "2021-05-01 08:00:00-05", |h| h[1].value = "1.5".into());
+        assert_eq!(mirror.sync(&ids).revised, vec![id.clone()]);
+        assert_eq!(mirror.get(&id).unwrap().unwrap().observations[1].value, "1.5");
+
+        // An update that changed no observation fetches none.
+        update(&server, "2021-06-01 08:00:00-05", |_| ());
+        let requests = starts().len();
+        assert_eq!(mirror.sync(&ids).appended, vec![(id.clone(), 0)]);
+        assert_eq!(starts().len(), requests);
+
+        // A second update on the day of the stored copy is refetched in full.
+        update(&server, "2021-06-01 15:00:00-05", |h| h.push(observation(8, "8")));
+        assert_eq!(mirror.sync(&ids).revised, vec![id.clone()]);
+        assert_eq!(starts()[requests..], [None]);
+        assert_eq!(mirror.get(&id).unwrap().unwrap().observations.len(), 8);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sync_catalog_uses_catalog_metadata() {
+        let dir = std::env::temp_dir().join(format!("fred_api_mirror_catalog_{}", std::process::id()));
+        let server = server((1..=3).map(|m| observation(m, &m.to_string())).collect());
+        let mut mirror = fake_mirror(&dir, &server);
+        mirror.fetch_item = Box::new(|id| Err(anyhow::anyhow!("Unexpected metadata request for [{}].", id)));
+
+        let mut catalog = Catalog::new();
+        catalog.insert_series(CategoryId::new(1), series_item(&server));
+        let id = SeriesId::new("AAA").unwrap();
+        assert_eq!(mirror.sync_catalog(&catalog).added, vec![id.clone()]);
+
+        update(&server, "2021-02-01 08:00:00-05", |h| h.push(observation(4, "4")));
+        catalog.insert_series(CategoryId::new(1), series_item(&server));
+        assert_eq!(mirror.sync_catalog(&catalog).appended, vec![(id, 1)]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+//! Several time series joined on a common date index, one column per series.
+//!
+//! ```
+//! let unrate = FredClient::time_series(&SeriesId::new("LRUNTTTTAUM156S")?)?;
+//! let cpi = FredClient::time_series(&SeriesId::new("AUSCPIALLQINMEI")?)?;
+//! let panel = Panel::join_at(&[unrate, cpi], Join::Inner, &Frequency::Quarterly, Aggregation::Avg)?;
+//! println!("{}", panel);
+//! ```
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use std::{collections::BTreeSet, fmt};
+use crate::{Aggregation, Frequency, TimeSeries, Window};
+
+/// How the date index of a panel is built from the indexes of its series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Join {
+    /// Dates in every series.
+    Inner,
+    /// Dates in any series.
+    Outer,
+    /// Dates in the first series.
+    Left,
+}
+
+/// Time series on a common date index. Each column is a `TimeSeries` with its metadata, missing
+/// where the series has no observation on a date of the index.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Panel {
+    dates:                          Vec<NaiveDate>,
+    names:                          Vec<String>,
+    columns:                        Vec<TimeSeries>,
+}
+
+impl Panel {
+    /// Join series, naming each column by its series id. Fails if a series has no metadata.
+    pub fn join(series: &[TimeSeries], how: Join) -> Result<Panel> {
+        let columns = series
+            .iter()
+            .map(|ts| {
+                let id = ts.id().ok_or_else(|| anyhow!("A series without metadata needs a column name."))?;
+                Ok((id.to_string(), ts.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Panel::join_named(columns, how)
+    }
+
+    /// Join named series. Fails if a name is repeated.
+    pub fn join_named(columns: Vec<(String, TimeSeries)>, how: Join) -> Result<Panel> {
+        let mut seen = BTreeSet::new();
+        if let Some((name, _)) = columns.iter().find(|(name, _)| !seen.insert(name.as_str())) {
+            return Err(anyhow!("Column [{}] is repeated.", name))
+        }
+
+        let dates: Vec<NaiveDate> = match how {
+            Join::Inner => match columns.split_first() {
+                Some(((_, first), rest)) => first
+                    .dates()
+                    .iter()
+                    .filter(|date| rest.iter().all(|(_, ts)| ts.position(**date).is_some()))
+                    .copied()
+                    .collect(),
+                None => Vec::new(),
+            },
+            Join::Outer => columns
+                .iter()
+                .flat_map(|(_, ts)| ts.dates().iter().copied())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+            Join::Left => columns.first().map(|(_, ts)| ts.dates().to_vec()).unwrap_or_default(),
+        };
+
+        let (names, columns) = columns.into_iter().map(|(name, ts)| (name, ts.reindex(&dates))).unzip();
+        Ok(Panel { dates, names, columns })
+    }
+
+    /// Resample every series to `frequency` with `method`, then join. Series at a lower frequency
+    /// are joined as they are, with one value per period. To aggregate series differently,
+    /// resample them before joining.
+    pub fn join_at(series: &[TimeSeries], how: Join, frequency: &Frequency, method: Aggregation) -> Result<Panel> {
+        let series = series
+            .iter()
+            .map(|ts| at_frequency(ts, frequency, method))
+            .collect::<Result<Vec<_>>>()?;
+        Panel::join(&series, how)
+    }
+
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    /// The column names in order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn columns(&self) -> &[TimeSeries] {
+        &self.columns
+    }
+
+    pub fn column(&self, name: &str) -> Option<&TimeSeries> {
+        self.names.iter().position(|n| n == name).map(|i| &self.columns[i])
+    }
+
+    /// The value of column `name` on `date`.
+    pub fn get(&self, date: NaiveDate, name: &str) -> Option<f64> {
+        self.column(name).and_then(|ts| ts.get(date))
+    }
+
+    /// The values on `date`, in column order.
+    pub fn row(&self, date: NaiveDate) -> Option<Vec<Option<f64>>> {
+        let i = self.dates.binary_search(&date).ok()?;
+        Some(self.columns.iter().map(|ts| ts.values()[i]).collect())
+    }
+
+    /// Dates and values in date order.
+    pub fn rows(&self) -> impl Iterator<Item = (NaiveDate, Vec<Option<f64>>)> + '_ {
+        self.dates
+            .iter()
+            .enumerate()
+            .map(move |(i, date)| (*date, self.columns.iter().map(|ts| ts.values()[i]).collect()))
+    }
+
+    /// Only the dates with a value in every column.
+    pub fn complete(&self) -> Panel {
+        let dates: Vec<NaiveDate> = self
+            .rows()
+            .filter(|(_, row)| row.iter().all(|value| value.is_some()))
+            .map(|(date, _)| date)
+            .collect();
+        Panel {
+            names: self.names.clone(),
+            columns: self.columns.iter().map(|ts| ts.reindex(&dates)).collect(),
+            dates,
+        }
+    }
+
+    /// Apply `f` to each column, such as a rolling statistic. The results are aligned to the dates
+    /// of the panel.
+    ///
+    /// ```
+    /// let means = panel.map(|ts| ts.rolling(Window::Months(12)).mean());
+    /// ```
+    pub fn map<F: Fn(&TimeSeries) -> TimeSeries>(&self, f: F) -> Panel {
+        Panel {
+            dates: self.dates.clone(),
+            names: self.names.clone(),
+            columns: self.columns.iter().map(|ts| f(ts).reindex(&self.dates)).collect(),
+        }
+    }
+
+    /// The correlation of columns `a` and `b` over `window`, with the default minimum number of
+    /// values. Use `Rolling::corr` on the columns to set the minimum.
+    pub fn rolling_corr(&self, a: &str, b: &str, window: Window) -> Result<TimeSeries> {
+        let column = |name: &str| self.column(name).ok_or_else(|| anyhow!("Column [{}] is not in the panel.", name));
+        Ok(column(a)?.rolling(window).corr(column(b)?))
+    }
+
+    /// The number of dates.
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+}
+
+// `ts` resampled to `frequency`, or `ts` itself if it has a lower frequency.
+pub(crate) fn at_frequency(ts: &TimeSeries, frequency: &Frequency, method: Aggregation) -> Result<TimeSeries> {
+    let per_year = ts.frequency().and_then(|source| source.per_year());
+    if per_year.zip(frequency.per_year()).is_some_and(|(from, to)| from < to) {
+        return Ok(ts.clone())
+    }
+    ts.resample(frequency, method)
+}
+
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::from("date");
+        for name in &self.names {
+            s.push_str(", ");
+            s.push_str(name);
+        }
+        for (date, row) in self.rows() {
+            s.push('\n');
+            s.push_str(&date.to_string());
+            for value in row {
+                s.push_str(", ");
+                if let Some(value) = value {
+                    s.push_str(&value.to_string());
+                }
+            }
+        }
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, NaiveDate};
+    use crate::{Aggregation, Frequency, Join, Panel, SeriesItem, TimeSeries, Window};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn meta(id: &str, frequency: &str) -> SeriesItem {
+        SeriesItem { frequency: frequency.into(), ..crate::test::series_item(id) }
+    }
+
+    fn monthly(id: &str, months: std::ops::RangeInclusive<u32>) -> TimeSeries {
+        TimeSeries::new(months.map(|m| (date(2020, m, 1), Some(m as f64))).collect())
+            .unwrap()
+            .with_meta(meta(id, "Monthly"))
+    }
+
+    #[test]
+    fn join_semantics() {
+        let series = [monthly("A", 1..=4), monthly("B", 3..=6)];
+
+        let inner = Panel::join(&series, Join::Inner).unwrap();
+        assert_eq!(inner.dates(), &[date(2020, 3, 1), date(2020, 4, 1)]);
+        assert_eq!(inner.names(), &["A".to_string(), "B".to_string()]);
+
+        let outer = Panel::join(&series, Join::Outer).unwrap();
+        assert_eq!(outer.len(), 6);
+        assert_eq!(outer.row(date(2020, 1, 1)), Some(vec![Some(1.0), None]));
+        assert_eq!(outer.get(date(2020, 6, 1), "B"), Some(6.0));
+        assert_eq!(outer.column("B").unwrap().id().unwrap().as_str(), "B");
+        assert_eq!(outer.complete(), inner);
+
+        let left = Panel::join(&series, Join::Left).unwrap();
+        assert_eq!(left.dates(), series[0].dates());
+        assert_eq!(left.column("B").unwrap().missing(), 2);
+        assert_eq!(left.to_string().lines().nth(1), Some("2020-01-01, 1, "));
+
+        assert!(Panel::join(&[series[0].clone(), series[0].clone()], Join::Inner).is_err());
+        assert!(Panel::join(&[TimeSeries::default()], Join::Inner).is_err());
+    }
+
+    #[test]
+    fn rolling_statistics_on_columns() {
+        let panel = Panel::join(&[monthly("A", 1..=4), monthly("B", 3..=6)], Join::Outer).unwrap();
+
+        let counts = panel.map(|ts| ts.rolling(Window::Periods(2)).min_observations(1).count());
+        assert_eq!(counts.column("B").unwrap().values(), &[None, None, Some(1.0), Some(2.0), Some(2.0), Some(2.0)]);
+        assert_eq!(counts.dates(), panel.dates());
+
+        let r = panel.rolling_corr("A", "B", Window::Expanding).unwrap();
+        assert_eq!(r.values()[3], Some(1.0));
+        assert!(panel.rolling_corr("A", "C", Window::Expanding).is_err());
+    }
+
+    #[test]
+    fn join_at_harmonizes_frequency() {
+        // Business days of January and February 2020, valued 1.0 and 2.0.
+        let daily = TimeSeries::new(
+            (0..60)
+                .map(|i| date(2020, 1, 1) + Duration::days(i))
+                .filter(|d| chrono::Datelike::weekday(d).num_days_from_monday() < 5)
+                .map(|d| (d, Some(chrono::Datelike::month(&d) as f64)))
+                .collect()
+        ).unwrap().with_meta(meta("D", "Daily"));
+
+        let panel = Panel::join_at(&[monthly("M", 1..=3), daily], Join::Outer, &Frequency::Monthly, Aggregation::Avg)
+            .unwrap();
+        assert_eq!(panel.dates(), &[date(2020, 1, 1), date(2020, 2, 1), date(2020, 3, 1)]);
+        assert_eq!(panel.column("D").unwrap().values(), &[Some(1.0), Some(2.0), None]);
+        assert_eq!(panel.column("D").unwrap().meta.as_ref().unwrap().frequency, "Monthly");
+
+        let quarterly = TimeSeries::new(vec![(date(2020, 1, 1), Some(1.0)), (date(2020, 4, 1), Some(2.0))])
+            .unwrap()
+            .with_meta(meta("Q", "Quarterly"));
+        let panel = Panel::join_at(&[monthly("M", 1..=4), quarterly], Join::Left, &Frequency::Monthly, Aggregation::Avg)
+            .unwrap();
+        assert_eq!(panel.column("Q").unwrap().values(), &[Some(1.0), None, None, Some(2.0)]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Real-time datasets for backtesting. For each as-of date, every series is taken from the vintage
+//! that was current on that date, so a model only sees data that was available at the time.
+//!
+//! ```
+//! let ids = vec![SeriesId::new("GDP")?, SeriesId::new("UNRATE")?];
+//! let as_of = vec!["2008-12-31".to_string(), "2009-03-31".to_string()];
+//! let mut dataset = RealTimeDataset::new();
+//! let panel = dataset.panel(&ids, &as_of, LateHistory::Missing)?;
+//! ```
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::{FredClient, SeriesId, VintageMatrix};
+
+/// What to do for an as-of date before the first vintage of a series. Many series only have
+/// vintage history from the 1990s or later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LateHistory {
+    /// The series is `None` in the snapshot.
+    Missing,
+    /// Use the first vintage, keeping only observations dated on or before the as-of date. This
+    /// approximates the real-time data and may include later revisions.
+    FirstVintage,
+}
+
+/// A cache of series vintages, used to build real-time panels. Vintages are fetched once per
+/// series. The cache can be serialized to avoid refetching between runs.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RealTimeDataset {
+    vintages: HashMap<SeriesId, VintageMatrix>,
+}
+
+impl RealTimeDataset {
+    pub fn new() -> Self {
+        RealTimeDataset::default()
+    }
+
+    /// Add vintages fetched elsewhere to the cache.
+    pub fn insert(&mut self, series_id: SeriesId, vintages: VintageMatrix) {
+        self.vintages.insert(series_id, vintages);
+    }
+
+    /// True if vintages for `series_id` are cached.
+    pub fn contains(&self, series_id: &SeriesId) -> bool {
+        self.vintages.contains_key(series_id)
+    }
+
+    /// The vintages of a series, fetched with `FredClient::series_vintages()` if not cached.
+    pub fn vintages(&mut self, series_id: &SeriesId) -> Result<&VintageMatrix> {
+        if !self.vintages.contains_key(series_id) {
+            let matrix = FredClient::series_vintages(series_id)?;
+            self.vintages.insert(series_id.clone(), matrix);
+        }
+        Ok(&self.vintages[series_id])
+    }
+
+    /// Build a panel with a snapshot for each date in `as_of`.
+    pub fn panel(
+        &mut self,
+        series_ids: &[SeriesId],
+        as_of: &[String],
+        late_history: LateHistory) -> Result<RealTimePanel>
+    {
+        for id in series_ids {
+            self.vintages(id)?;
+        }
+
+        let mut snapshots = Vec::new();
+        for date in as_of {
+            let mut series = BTreeMap::new();
+            for id in series_ids {
+                let matrix = &self.vintages[id];
+                series.insert(id.clone(), snapshot(matrix, date, late_history));
+            }
+            snapshots.push(Snapshot { as_of: date.clone(), series });
+        }
+        Ok(RealTimePanel { snapshots })
+    }
+}
+
+fn snapshot(matrix: &VintageMatrix, date: &str, late_history: LateHistory) -> Option<Vec<(String, String)>> {
+    let to_owned = |obs: Vec<(&str, &str)>| -> Vec<(String, String)> {
+        obs.into_iter().map(|(d, v)| (d.to_string(), v.to_string())).collect()
+    };
+
+    let first_vintage = matrix.columns().first()?.vintage_date.as_str();
+    if first_vintage <= date {
+        return Some(to_owned(matrix.as_of(date)))
+    }
+    match late_history {
+        LateHistory::Missing => None,
+        LateHistory::FirstVintage => {
+            let obs = matrix
+                .as_of(first_vintage)
+                .into_iter()
+                .filter(|(d, _)| *d <= date)
+                .collect();
+            Some(to_owned(obs))
+        },
+    }
+}
+
+/// The data available for each series on a single as-of date.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub as_of:                      String,
+    /// `(date, value)` observations for each series, or `None` if the series was not available.
+    pub series:                     BTreeMap<SeriesId, Option<Vec<(String, String)>>>,
+}
+
+impl Snapshot {
+    /// The observations of a series as they were known on the as-of date.
+    pub fn get(&self, series_id: &SeriesId) -> Option<&[(String, String)]> {
+        self.series.get(series_id).and_then(|obs| obs.as_deref())
+    }
+}
+
+/// Snapshots in as-of date order.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RealTimePanel {
+    pub snapshots:                  Vec<Snapshot>,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{LateHistory, RealTimeDataset, SeriesId, VintageMatrix};
+
+    #[test]
+    fn panel_uses_vintage_current_at_each_date() {
+        let gdp = SeriesId::new("GDP").unwrap();
+        let late = SeriesId::new("LATE").unwrap();
+        let mut dataset = RealTimeDataset::new();
+        dataset.insert(gdp.clone(), serde_json::from_str::<VintageMatrix>(r#"[
+            {"date": "2008-07-01", "GDP_20081030": "100.0", "GDP_20081126": "101.0"},
+            {"date": "2008-10-01", "GDP_20090130": "102.0"}
+        ]"#).unwrap());
+        dataset.insert(late.clone(), serde_json::from_str::<VintageMatrix>(r#"[
+            {"date": "2008-07-01", "LATE_20090301": "5.0"},
+            {"date": "2009-01-01", "LATE_20090301": "6.0"}
+        ]"#).unwrap());
+
+        let ids = vec![gdp.clone(), late.clone()];
+        let as_of = vec!["2008-11-01".to_string(), "2009-02-01".to_string()];
+
+        let panel = dataset.panel(&ids, &as_of, LateHistory::Missing).unwrap();
+        assert_eq!(
+            panel.snapshots[0].get(&gdp).unwrap(),
+            &[("2008-07-01".to_string(), "100.0".to_string())],
+        );
+        assert_eq!(panel.snapshots[1].get(&gdp).unwrap().len(), 2);
+        assert_eq!(panel.snapshots[1].get(&late), None);
+
+        let panel = dataset.panel(&ids, &as_of, LateHistory::FirstVintage).unwrap();
+        assert_eq!(
+            panel.snapshots[0].get(&late).unwrap(),
+            &[("2008-07-01".to_string(), "5.0".to_string())],
+        );
+    }
+}
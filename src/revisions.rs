@@ -0,0 +1,230 @@
+//! Revision analysis across the vintages of a series: first and latest prints, revisions at fixed
+//! horizons after the first release, and summary statistics of those revisions.
+//!
+//! ```
+//! let analysis = FredClient::series_revisions(&SeriesId::new("PAYEMS")?, &[1, 3, 12])?;
+//! println!("{:?}", analysis.summary());
+//! println!("{:?}", analysis.horizon_summary(3));
+//! ```
+
+use anyhow::{anyhow, Result};
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::{FredClient, SeriesId, VintageMatrix};
+
+/// The revision history of a single observation date.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Revision {
+    pub date:                       String,
+    pub first_vintage:              String,
+    pub first:                      f64,
+    pub latest_vintage:             String,
+    pub latest:                     f64,
+    /// `(months, revision)` for each requested horizon. The revision is `None` when the horizon
+    /// lies beyond the last vintage.
+    pub horizons:                   Vec<(u32, Option<f64>)>,
+}
+
+impl Revision {
+    /// Latest print less first print.
+    pub fn revision(&self) -> f64 {
+        self.latest - self.first
+    }
+
+    /// `1` if revised up, `-1` if revised down and `0` if unchanged.
+    pub fn sign(&self) -> i8 {
+        let r = self.revision();
+        if r > 0.0 {
+            1
+        } else if r < 0.0 {
+            -1
+        } else {
+            0
+        }
+    }
+
+    /// The revision `months` after the first release, if that horizon was requested and reached.
+    pub fn at_horizon(&self, months: u32) -> Option<f64> {
+        self.horizons.iter().find(|(m, _)| *m == months).and_then(|(_, r)| *r)
+    }
+}
+
+/// Summary statistics of a set of revisions.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RevisionSummary {
+    pub count:                      usize,
+    /// Mean signed revision.
+    pub bias:                       f64,
+    pub mean_absolute:              f64,
+    /// Sample standard deviation of the signed revisions.
+    pub std_dev:                    f64,
+    pub upward:                     usize,
+    pub downward:                   usize,
+    pub unchanged:                  usize,
+}
+
+impl RevisionSummary {
+    fn from_revisions(revisions: &[f64]) -> Option<RevisionSummary> {
+        if revisions.is_empty() {
+            return None
+        }
+        let n = revisions.len() as f64;
+        let bias = revisions.iter().sum::<f64>() / n;
+        let mean_absolute = revisions.iter().map(|r| r.abs()).sum::<f64>() / n;
+        let std_dev = if revisions.len() > 1 {
+            (revisions.iter().map(|r| (r - bias).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        Some(
+            RevisionSummary {
+                count:          revisions.len(),
+                bias,
+                mean_absolute,
+                std_dev,
+                upward:         revisions.iter().filter(|r| **r > 0.0).count(),
+                downward:       revisions.iter().filter(|r| **r < 0.0).count(),
+                unchanged:      revisions.iter().filter(|r| **r == 0.0).count(),
+            }
+        )
+    }
+}
+
+impl fmt::Display for RevisionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+            "count: {}\nbias: {}\nmean_absolute: {}\nstd_dev: {}\n\
+                upward: {}\ndownward: {}\nunchanged: {}",
+            self.count,
+            self.bias,
+            self.mean_absolute,
+            self.std_dev,
+            self.upward,
+            self.downward,
+            self.unchanged,
+        )
+    }
+}
+
+/// Revisions for every observation date of a series.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RevisionAnalysis {
+    pub revisions:                  Vec<Revision>,
+}
+
+impl RevisionAnalysis {
+    /// Analyse the vintages in `matrix`, with revisions at each of `horizons` months after the
+    /// first release. Values FRED reports as missing (`"."`) are skipped.
+    pub fn from_matrix(matrix: &VintageMatrix, horizons: &[u32]) -> Result<RevisionAnalysis> {
+        let last_vintage = match matrix.columns().last() {
+            Some(col) => parse_date(&col.vintage_date)?,
+            None => return Ok(RevisionAnalysis { revisions: Vec::new() }),
+        };
+
+        let mut revisions = Vec::new();
+        for date in matrix.dates() {
+            let prints: Vec<(&str, f64)> = matrix
+                .row(date)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(vintage, value)| value.parse::<f64>().ok().map(|v| (vintage, v)))
+                .collect();
+
+            let (first_vintage, first) = match prints.first() {
+                Some(print) => *print,
+                None => continue,
+            };
+            let (latest_vintage, latest) = prints[prints.len() - 1];
+
+            let first_date = parse_date(first_vintage)?;
+            let mut at_horizons = Vec::new();
+            for months in horizons {
+                let revision = first_date
+                    .checked_add_months(Months::new(*months))
+                    .filter(|target| *target <= last_vintage)
+                    .map(|target| target.format("%Y-%m-%d").to_string())
+                    .and_then(|target| {
+                        prints
+                            .iter()
+                            .filter(|(vintage, _)| *vintage <= target.as_str())
+                            .map(|(_, v)| *v)
+                            .next_back()
+                    })
+                    .map(|v| v - first);
+                at_horizons.push((*months, revision));
+            }
+
+            revisions.push(
+                Revision {
+                    date:               date.clone(),
+                    first_vintage:      first_vintage.to_string(),
+                    first,
+                    latest_vintage:     latest_vintage.to_string(),
+                    latest,
+                    horizons:           at_horizons,
+                }
+            );
+        }
+        Ok(RevisionAnalysis { revisions })
+    }
+
+    /// Summary statistics of latest print less first print, or `None` if there are no revisions.
+    pub fn summary(&self) -> Option<RevisionSummary> {
+        let revisions: Vec<f64> = self.revisions.iter().map(|r| r.revision()).collect();
+        RevisionSummary::from_revisions(&revisions)
+    }
+
+    /// Summary statistics of revisions `months` after the first release, over the observation
+    /// dates where that horizon has been reached.
+    pub fn horizon_summary(&self, months: u32) -> Option<RevisionSummary> {
+        let revisions: Vec<f64> = self.revisions.iter().filter_map(|r| r.at_horizon(months)).collect();
+        RevisionSummary::from_revisions(&revisions)
+    }
+}
+
+impl FredClient {
+    /// Fetch every vintage of a series and analyse its revisions at `horizons` months after the
+    /// first release.
+    pub fn series_revisions(series_id: &SeriesId, horizons: &[u32]) -> Result<RevisionAnalysis> {
+        RevisionAnalysis::from_matrix(&FredClient::series_vintages(series_id)?, horizons)
+    }
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Expected date [{}] to be YYYY-MM-DD.", s))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{RevisionAnalysis, VintageMatrix};
+
+    #[test]
+    fn revisions_at_horizons() {
+        let matrix: VintageMatrix = serde_json::from_str(r#"[
+            {"date": "2008-07-01", "GDP_20081030": "100.0", "GDP_20081126": "101.0", "GDP_20090731": "99.0"},
+            {"date": "2008-10-01", "GDP_20090130": "200.0", "GDP_20090227": "."}
+        ]"#).unwrap();
+        let analysis = RevisionAnalysis::from_matrix(&matrix, &[1, 3, 12]).unwrap();
+
+        let q3 = &analysis.revisions[0];
+        assert_eq!(q3.first, 100.0);
+        assert_eq!(q3.latest, 99.0);
+        assert_eq!(q3.sign(), -1);
+        assert_eq!(q3.at_horizon(1), Some(1.0));
+        assert_eq!(q3.at_horizon(3), Some(1.0));
+        assert_eq!(q3.at_horizon(12), None);
+
+        let q4 = &analysis.revisions[1];
+        assert_eq!(q4.revision(), 0.0);
+        assert_eq!(q4.at_horizon(3), Some(0.0));
+
+        let summary = analysis.summary().unwrap();
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.bias, -0.5);
+        assert_eq!(summary.downward, 1);
+        assert_eq!(summary.unchanged, 1);
+        assert!((summary.std_dev - 0.5_f64.sqrt()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,218 @@
+//! Offline full-text search over the series in a `Catalog`. Titles, tags, units and notes are
+//! searched, in that order of weight. Every query term must match, either as a whole word or as
+//! the prefix of one.
+//!
+//! ```
+//! let catalog = Catalog::load("catalog")?;
+//! let query = SearchQuery::new("consumer price ind")
+//!     .frequency(Frequency::Monthly)
+//!     .seasonal_adjustment(SeasonalAdjustment::SA)
+//!     .observation_end_after("2020-01-01");
+//! let results = catalog.search(&query).exclude_phrases(vec!["Discontinued"]);
+//! ```
+
+use std::cmp::Ordering;
+use crate::{
+    Catalog,
+    Frequency,
+    SeasonalAdjustment,
+    SeriesItem,
+    SeriesItems,
+};
+
+// Field weights. A prefix match scores half of a whole word match.
+const TITLE_WEIGHT: f64 = 4.0;
+const TAGS_WEIGHT: f64 = 2.0;
+const UNITS_WEIGHT: f64 = 1.0;
+const NOTES_WEIGHT: f64 = 0.5;
+
+/// A full-text query with optional field filters.
+#[derive(Clone, Debug, Default)]
+pub struct SearchQuery {
+    terms:                  Vec<String>,
+    frequency:              Option<Frequency>,
+    seasonal_adjustment:    Option<SeasonalAdjustment>,
+    observation_end_after:  Option<String>,
+    limit:                  Option<usize>,
+}
+
+impl SearchQuery {
+    /// A query for the words of `text`. Case and punctuation are ignored.
+    pub fn new(text: &str) -> Self {
+        SearchQuery { terms: tokenize(text), ..SearchQuery::default() }
+    }
+
+    pub fn frequency(mut self, frequency: Frequency) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    pub fn seasonal_adjustment(mut self, sa: SeasonalAdjustment) -> Self {
+        self.seasonal_adjustment = Some(sa);
+        self
+    }
+
+    /// Only include series with observations after `date`, as `YYYY-MM-DD`.
+    pub fn observation_end_after(mut self, date: &str) -> Self {
+        self.observation_end_after = Some(date.to_string());
+        self
+    }
+
+    /// The maximum number of results.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn accepts(&self, series: &SeriesItem) -> bool {
+        self.frequency.as_ref().is_none_or(|f| f.matches(&series.frequency_kind()))
+            && self.seasonal_adjustment.as_ref().is_none_or(|sa| series.seasonal_adjustment_kind() == *sa)
+            && self.observation_end_after.as_ref().is_none_or(|date| series.observation_end.as_str() > date.as_str())
+    }
+
+    // The relevance of `fields`, or `None` if a term does not match.
+    fn score(&self, fields: &[(f64, Vec<String>)]) -> Option<f64> {
+        let mut total = 0.0;
+        for term in &self.terms {
+            let mut best = 0.0;
+            for (weight, words) in fields {
+                for word in words {
+                    let score = if word == term {
+                        *weight
+                    } else if word.starts_with(term.as_str()) {
+                        weight / 2.0
+                    } else {
+                        continue
+                    };
+                    best = f64::max(best, score);
+                }
+            }
+            if best == 0.0 {
+                return None
+            }
+            total += best;
+        }
+        Some(total)
+    }
+}
+
+impl Catalog {
+    /// Series matching `query`, most relevant first. Ties are broken by popularity. A query with no
+    /// terms returns every series that passes the filters.
+    pub fn search(&self, query: &SearchQuery) -> SeriesItems {
+        SeriesItems(
+            self.search_scored(query)
+                .into_iter()
+                .map(|(_, series)| series.clone())
+                .collect()
+        )
+    }
+
+    /// As `search()`, with the relevance score of each series.
+    pub fn search_scored(&self, query: &SearchQuery) -> Vec<(f64, &SeriesItem)> {
+        let mut results: Vec<(f64, &SeriesItem)> = self.series
+            .values()
+            .filter(|series| query.accepts(series))
+            .filter_map(|series| {
+                let tags = self
+                    .tags_of(&series.id)
+                    .iter()
+                    .flat_map(|tag| tokenize(tag.as_str()))
+                    .collect();
+                let fields = [
+                    (TITLE_WEIGHT, tokenize(&series.title)),
+                    (TAGS_WEIGHT, tags),
+                    (UNITS_WEIGHT, tokenize(&series.units)),
+                    (NOTES_WEIGHT, tokenize(series.notes.as_deref().unwrap_or(""))),
+                ];
+                query.score(&fields).map(|score| (score, series))
+            })
+            .collect();
+
+        results.sort_by(|(a, sa), (b, sb)| {
+            b.partial_cmp(a)
+                .unwrap_or(Ordering::Equal)
+                .then(sb.popularity.cmp(&sa.popularity))
+                .then(sa.id.cmp(&sb.id))
+        });
+        if let Some(limit) = query.limit {
+            results.truncate(limit);
+        }
+        results
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Catalog,
+        CategoryId,
+        Frequency,
+        SearchQuery,
+        SeriesId,
+        SeriesItem,
+        TagName,
+    };
+
+    fn series_item(id: &str, title: &str, frequency: &str, observation_end: &str, popularity: isize) -> SeriesItem {
+        SeriesItem {
+            title:                      title.into(),
+            observation_end:            observation_end.into(),
+            frequency:                  frequency.into(),
+            popularity,
+            notes:                      Some("Measures prices paid by urban consumers.".into()),
+            ..crate::test::series_item(id)
+        }
+    }
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::new();
+        let id = CategoryId::new(1);
+        catalog.insert_series(id, series_item("CPIAUCSL", "Consumer Price Index for All Urban Consumers", "Monthly", "2020-12-01", 90));
+        catalog.insert_series(id, series_item("CPIQ", "Consumer Price Index", "Quarterly", "2020-10-01", 10));
+        catalog.insert_series(id, series_item("OLD", "Consumer Price Index (Discontinued)", "Monthly", "1990-12-01", 50));
+        catalog.insert_series(id, series_item("PPI", "Producer Price Index", "Monthly", "2020-12-01", 80));
+        catalog.series_tags.insert(SeriesId::new("PPI").unwrap(), vec![TagName::new("inflation").unwrap()]);
+        catalog
+    }
+
+    fn ids(catalog: &Catalog, query: &SearchQuery) -> Vec<String> {
+        catalog.search(query).iter().map(|s| s.id.to_string()).collect()
+    }
+
+    #[test]
+    fn search_ranks_prefix_matches_and_filters() {
+        let catalog = catalog();
+
+        assert_eq!(ids(&catalog, &SearchQuery::new("consumer price")), vec!["CPIAUCSL", "OLD", "CPIQ", "PPI"]);
+        assert_eq!(ids(&catalog, &SearchQuery::new("urb")), vec!["CPIAUCSL", "PPI", "OLD", "CPIQ"]);
+        assert_eq!(ids(&catalog, &SearchQuery::new("inflation")), vec!["PPI"]);
+
+        let query = SearchQuery::new("price index")
+            .frequency(Frequency::Monthly)
+            .observation_end_after("2000-01-01");
+        assert_eq!(ids(&catalog, &query), vec!["CPIAUCSL", "PPI"]);
+
+        let results = catalog
+            .search(&SearchQuery::new("consumer"))
+            .exclude_phrases(vec!["Discontinued"]);
+        assert_eq!(results.inner().len(), 3);
+    }
+
+    #[test]
+    fn weekly_filter_matches_any_ending_day() {
+        let mut catalog = catalog();
+        let weekly = series_item("ICSA", "Initial Claims", "Weekly, Ending Saturday", "2020-12-26", 70);
+        catalog.insert_series(CategoryId::new(1), weekly);
+
+        assert_eq!(ids(&catalog, &SearchQuery::new("claims").frequency(Frequency::Weekly)), vec!["ICSA"]);
+        assert!(ids(&catalog, &SearchQuery::new("claims").frequency(Frequency::Monthly)).is_empty());
+    }
+}
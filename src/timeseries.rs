@@ -0,0 +1,235 @@
+//! A time series of `Option<f64>` values on a sorted date index, with the metadata of the series.
+//! Missing observations (`"."`) are `None`.
+//!
+//! ```
+//! let gdp = FredClient::time_series(&SeriesId::new("GDP")?)?;
+//! let recent = gdp.slice(date(2010, 1, 1), date(2019, 12, 31));
+//! println!("{:?}", recent.last_valid());
+//! ```
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::{
+    FredClient,
+    Frequency,
+    SeriesId,
+    SeriesItem,
+    SeriesObservations,
+};
+
+mod fill;
+mod resample;
+mod rolling;
+mod transform;
+
+pub use fill::{Fill, Gap};
+pub use resample::{Aggregation, Incomplete};
+pub use rolling::{Rolling, Window};
+pub use transform::Transformation;
+
+/// A time series with a sorted, unique date index.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TimeSeries {
+    pub meta:                       Option<SeriesItem>,
+    dates:                          Vec<NaiveDate>,
+    values:                         Vec<Option<f64>>,
+}
+
+impl TimeSeries {
+    /// A time series from date-value pairs in any order. Fails on a repeated date.
+    pub fn new(mut pairs: Vec<(NaiveDate, Option<f64>)>) -> Result<Self> {
+        pairs.sort_by_key(|(date, _)| *date);
+        if let Some(pair) = pairs.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(anyhow!("Date [{}] is repeated.", pair[0].0))
+        }
+        let (dates, values) = pairs.into_iter().unzip();
+        Ok(TimeSeries { meta: None, dates, values })
+    }
+
+    // Build from a date index that is already sorted and unique.
+    pub(crate) fn from_parts(dates: Vec<NaiveDate>, values: Vec<Option<f64>>, meta: Option<SeriesItem>) -> Self {
+        debug_assert!(dates.windows(2).all(|pair| pair[0] < pair[1]));
+        debug_assert_eq!(dates.len(), values.len());
+        TimeSeries { meta, dates, values }
+    }
+
+    /// A time series from the observations of a series. Values that are not numbers are missing.
+    pub fn from_observations(observations: &SeriesObservations) -> Result<Self> {
+        let pairs = observations.observations
+            .iter()
+            .map(|obs| Ok((parse_date(&obs.date)?, obs.value.parse::<f64>().ok())))
+            .collect::<Result<Vec<_>>>()?;
+        TimeSeries::new(pairs)
+    }
+
+    pub fn with_meta(mut self, meta: SeriesItem) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// A series with the same metadata and new values on a new index.
+    pub(crate) fn with_parts(&self, dates: Vec<NaiveDate>, values: Vec<Option<f64>>) -> Self {
+        TimeSeries::from_parts(dates, values, self.meta.clone())
+    }
+
+    /// The series id, if there is metadata.
+    pub fn id(&self) -> Option<&SeriesId> {
+        self.meta.as_ref().map(|meta| &meta.id)
+    }
+
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    pub fn values(&self) -> &[Option<f64>] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// The position of `date` in the index.
+    pub fn position(&self, date: NaiveDate) -> Option<usize> {
+        self.dates.binary_search(&date).ok()
+    }
+
+    /// The value on `date`, or `None` if the date is not in the index or the value is missing.
+    pub fn get(&self, date: NaiveDate) -> Option<f64> {
+        self.position(date).and_then(|i| self.values[i])
+    }
+
+    /// The observations from `start` to `end` inclusive.
+    pub fn slice(&self, start: NaiveDate, end: NaiveDate) -> TimeSeries {
+        let from = self.dates.partition_point(|date| *date < start);
+        let to = self.dates.partition_point(|date| *date <= end).max(from);
+        self.with_parts(self.dates[from..to].to_vec(), self.values[from..to].to_vec())
+    }
+
+    // The values on `dates`, which must be sorted and unique, missing where `dates` is not in the
+    // index.
+    pub(crate) fn reindex(&self, dates: &[NaiveDate]) -> TimeSeries {
+        let values = dates.iter().map(|date| self.get(*date)).collect();
+        self.with_parts(dates.to_vec(), values)
+    }
+
+    /// The first date with a value, and the value.
+    pub fn first_valid(&self) -> Option<(NaiveDate, f64)> {
+        self.valid().next()
+    }
+
+    /// The last date with a value, and the value.
+    pub fn last_valid(&self) -> Option<(NaiveDate, f64)> {
+        self.valid().next_back()
+    }
+
+    /// Date-value pairs in date order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (NaiveDate, Option<f64>)> + '_ {
+        self.dates.iter().copied().zip(self.values.iter().copied())
+    }
+
+    /// Date-value pairs with a value, in date order.
+    pub fn valid(&self) -> impl DoubleEndedIterator<Item = (NaiveDate, f64)> + '_ {
+        self.iter().filter_map(|(date, value)| value.map(|value| (date, value)))
+    }
+
+    /// The number of missing values.
+    pub fn missing(&self) -> usize {
+        self.values.iter().filter(|value| value.is_none()).count()
+    }
+
+    /// The frequency from the metadata, or else inferred from the median spacing of the dates.
+    pub fn frequency(&self) -> Option<Frequency> {
+        if let Some(meta) = &self.meta {
+            match meta.frequency_kind() {
+                Frequency::Other(_) => (),
+                frequency => return Some(frequency),
+            }
+        }
+        let mut gaps: Vec<i64> = self.dates.windows(2).map(|pair| (pair[1] - pair[0]).num_days()).collect();
+        if gaps.is_empty() {
+            return None
+        }
+        gaps.sort_unstable();
+        match gaps[gaps.len() / 2] {
+            1..=4 => Some(Frequency::Daily),
+            5..=8 => Some(Frequency::Weekly),
+            13..=15 => Some(Frequency::Biweekly),
+            28..=31 => Some(Frequency::Monthly),
+            89..=92 => Some(Frequency::Quarterly),
+            181..=184 => Some(Frequency::Semiannual),
+            365..=366 => Some(Frequency::Annual),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimeSeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(meta) = &self.meta {
+            writeln!(f, "{}: {}", meta.id, meta.title)?;
+        }
+        for (date, value) in self.iter() {
+            match value {
+                Some(value) => writeln!(f, "{} {}", date, value)?,
+                None => writeln!(f, "{} .", date)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FredClient {
+    /// Get the observations of a series as a `TimeSeries`, with its metadata.
+    pub fn time_series(series_id: &SeriesId) -> Result<TimeSeries> {
+        let meta = FredClient::series(series_id)?
+            .seriess
+            .0
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Series [{}] was not found.", series_id))?;
+        let observations = FredClient::series_observations(series_id)?;
+        Ok(TimeSeries::from_observations(&observations)?.with_meta(meta))
+    }
+}
+
+pub(crate) fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| anyhow!("Failed to parse date [{}].", date))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use crate::{SeriesObservations, TimeSeries};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn time_series_from_observations() {
+        let observations: SeriesObservations =
+            serde_json::from_str(include_str!("../tests/fixtures/series_observations.json")).unwrap();
+        let ts = TimeSeries::from_observations(&observations).unwrap();
+
+        assert_eq!(ts.len(), 4);
+        assert_eq!(ts.missing(), 1);
+        assert_eq!(ts.get(date(1930, 1, 1)), Some(975.5));
+        assert_eq!(ts.get(date(1931, 1, 1)), None);
+        assert_eq!(ts.first_valid(), Some((date(1929, 1, 1), 1065.9)));
+        assert_eq!(ts.last_valid(), Some((date(1932, 1, 1), 778.3)));
+
+        let slice = ts.slice(date(1929, 6, 1), date(1931, 1, 1));
+        assert_eq!(slice.dates(), &[date(1930, 1, 1), date(1931, 1, 1)]);
+        assert_eq!(slice.last_valid(), Some((date(1930, 1, 1), 975.5)));
+        assert!(ts.slice(date(1940, 1, 1), date(1930, 1, 1)).is_empty());
+
+        assert!(TimeSeries::new(vec![(date(2000, 1, 1), None), (date(2000, 1, 1), Some(1.0))]).is_err());
+    }
+}
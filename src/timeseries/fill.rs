@@ -0,0 +1,183 @@
+//! Filling missing values. A gap is a run of missing values in the index. Dates absent from the
+//! index, such as months skipped during a government shutdown, are not gaps until `regularize`
+//! adds them.
+//!
+//! ```
+//! let ts = FredClient::time_series(&SeriesId::new("UNRATE")?)?.regularize()?;
+//! for gap in ts.gaps() {
+//!     println!("{}", gap);
+//! }
+//! let filled = ts.fill_limit(Fill::Linear, 2);
+//! ```
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use std::{collections::BTreeSet, fmt};
+use super::resample::next_observation;
+use crate::TimeSeries;
+
+/// How to fill a gap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fill {
+    /// The last value before the gap. Leading gaps are not filled.
+    Forward,
+    /// The first value after the gap. Trailing gaps are not filled.
+    Backward,
+    /// Interpolated in time between the values either side of the gap. Leading and trailing
+    /// gaps are not filled.
+    Linear,
+}
+
+/// A run of missing values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gap {
+    /// The first missing date.
+    pub start:                      NaiveDate,
+    /// The last missing date.
+    pub end:                        NaiveDate,
+    /// The number of missing values.
+    pub len:                        usize,
+}
+
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} to {} ({} missing)", self.start, self.end, self.len)
+    }
+}
+
+impl TimeSeries {
+    /// The gaps in date order.
+    pub fn gaps(&self) -> Vec<Gap> {
+        self.gap_ranges()
+            .map(|(i, j)| Gap { start: self.dates()[i], end: self.dates()[j - 1], len: j - i })
+            .collect()
+    }
+
+    /// Fill every gap that `method` can fill.
+    pub fn fill(&self, method: Fill) -> TimeSeries {
+        self.fill_gaps(method, None)
+    }
+
+    /// Fill gaps of at most `limit` missing values. Longer gaps are left missing.
+    pub fn fill_limit(&self, method: Fill, limit: usize) -> TimeSeries {
+        self.fill_gaps(method, Some(limit))
+    }
+
+    /// Add the dates missing from the index at the frequency of the series, with missing values.
+    /// Daily series are taken to have business day observations. Fails if the frequency is not
+    /// known.
+    pub fn regularize(&self) -> Result<TimeSeries> {
+        let (first, last) = match (self.dates().first(), self.dates().last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Ok(self.clone()),
+        };
+        let frequency = self.frequency().ok_or_else(|| anyhow!("The frequency of the series is not known."))?;
+        let mut dates: BTreeSet<NaiveDate> = self.dates().iter().copied().collect();
+        let mut date = first;
+        while let Some(next) = next_observation(date, &frequency).filter(|next| *next <= last) {
+            dates.insert(next);
+            date = next;
+        }
+        let dates: Vec<NaiveDate> = dates.into_iter().collect();
+        Ok(self.reindex(&dates))
+    }
+
+    // The start and end (exclusive) positions of each gap.
+    fn gap_ranges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let values = self.values();
+        let mut i = 0;
+        std::iter::from_fn(move || {
+            while i < values.len() && values[i].is_some() {
+                i += 1;
+            }
+            if i == values.len() {
+                return None
+            }
+            let start = i;
+            while i < values.len() && values[i].is_none() {
+                i += 1;
+            }
+            Some((start, i))
+        })
+    }
+
+    fn fill_gaps(&self, method: Fill, limit: Option<usize>) -> TimeSeries {
+        let dates = self.dates();
+        let mut values = self.values().to_vec();
+        for (i, j) in self.gap_ranges() {
+            if limit.is_some_and(|limit| j - i > limit) {
+                continue
+            }
+            let before = i.checked_sub(1).map(|k| (dates[k], values[k].unwrap()));
+            let after = values.get(j).map(|value| (dates[j], value.unwrap()));
+            for k in i..j {
+                values[k] = match (method, before, after) {
+                    (Fill::Forward, Some((_, x)), _) => Some(x),
+                    (Fill::Backward, _, Some((_, y))) => Some(y),
+                    (Fill::Linear, Some((d0, x)), Some((d1, y))) => {
+                        let t = (dates[k] - d0).num_days() as f64 / (d1 - d0).num_days() as f64;
+                        Some(x + (y - x) * t)
+                    },
+                    _ => None,
+                };
+            }
+        }
+        self.with_parts(dates.to_vec(), values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use crate::{Fill, Gap, TimeSeries};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn fill_gaps() {
+        // October 2013 is absent, as in series affected by the government shutdown.
+        let ts = TimeSeries::new(vec![
+            (date(2013, 6, 1), None),
+            (date(2013, 7, 1), Some(1.0)),
+            (date(2013, 8, 1), None),
+            (date(2013, 9, 1), Some(3.0)),
+            (date(2013, 11, 1), Some(6.0)),
+            (date(2013, 12, 1), None),
+        ]).unwrap().regularize().unwrap();
+
+        assert_eq!(ts.len(), 7);
+        assert_eq!(ts.gaps(), vec![
+            Gap { start: date(2013, 6, 1), end: date(2013, 6, 1), len: 1 },
+            Gap { start: date(2013, 8, 1), end: date(2013, 8, 1), len: 1 },
+            Gap { start: date(2013, 10, 1), end: date(2013, 10, 1), len: 1 },
+            Gap { start: date(2013, 12, 1), end: date(2013, 12, 1), len: 1 },
+        ]);
+
+        assert_eq!(
+            ts.fill(Fill::Forward).values(),
+            &[None, Some(1.0), Some(1.0), Some(3.0), Some(3.0), Some(6.0), Some(6.0)],
+        );
+        assert_eq!(
+            ts.fill(Fill::Backward).values(),
+            &[Some(1.0), Some(1.0), Some(3.0), Some(3.0), Some(6.0), Some(6.0), None],
+        );
+        // September to November is 61 days, of which 30 are before October 1.
+        let linear = ts.fill(Fill::Linear);
+        assert_eq!(linear.values()[2], Some(2.0));
+        assert_eq!(linear.values()[4], Some(3.0 + 3.0 * (30.0 / 61.0)));
+        assert_eq!(linear.values()[6], None);
+    }
+
+    #[test]
+    fn fill_limit_skips_long_gaps() {
+        let ts = TimeSeries::new(
+            (1..=8).map(|m| (date(2020, m, 1), if (3..=5).contains(&m) { None } else { Some(m as f64) })).collect()
+        ).unwrap();
+        assert_eq!(ts.gaps(), vec![Gap { start: date(2020, 3, 1), end: date(2020, 5, 1), len: 3 }]);
+        assert_eq!(ts.fill_limit(Fill::Forward, 2), ts);
+        assert_eq!(ts.fill_limit(Fill::Forward, 3).missing(), 0);
+        assert_eq!(ts.gaps()[0].to_string(), "2020-03-01 to 2020-05-01 (3 missing)");
+    }
+}
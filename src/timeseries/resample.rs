@@ -0,0 +1,242 @@
+//! Conversion to a lower frequency, as FRED does with the `frequency` and `aggregation_method`
+//! request parameters. Monthly and lower frequencies are labelled by the first day of the period.
+//! Weekly and biweekly periods are labelled by their ending day, `Weekly` ending on Friday and
+//! `Biweekly` on Wednesday as in FRED.
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use std::fmt;
+use crate::{Frequency, TimeSeries, Weekday};
+
+/// A value of the FRED `aggregation_method` request parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Aggregation {
+    /// The mean of the values in the period.
+    Avg,
+    /// The sum of the values in the period.
+    Sum,
+    /// The last value in the period.
+    Eop,
+}
+
+impl Aggregation {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Aggregation::Avg => "avg",
+            Aggregation::Sum => "sum",
+            Aggregation::Eop => "eop",
+        }
+    }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// What to do with a trailing period that the series does not yet cover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Incomplete {
+    Drop,
+    Keep,
+}
+
+impl TimeSeries {
+    /// Aggregate to `frequency`, dropping an incomplete trailing period.
+    pub fn resample(&self, frequency: &Frequency, method: Aggregation) -> Result<TimeSeries> {
+        self.resample_with(frequency, method, Incomplete::Drop)
+    }
+
+    /// Aggregate to `frequency`. Missing values are skipped, and a period with no values is
+    /// missing. The trailing period is incomplete if the next observation at the frequency of
+    /// this series would still fall inside it. Fails if `frequency` is higher than the frequency
+    /// of this series.
+    pub fn resample_with(&self, frequency: &Frequency, method: Aggregation, incomplete: Incomplete) -> Result<TimeSeries> {
+        if let Some(source) = self.frequency() {
+            if let (Some(from), Some(to)) = (source.per_year(), frequency.per_year()) {
+                if to > from {
+                    return Err(anyhow!("Cannot resample from [{}] to the higher frequency [{}].", source, frequency))
+                }
+            }
+        }
+        let mut dates = Vec::new();
+        let mut values = Vec::new();
+        let mut period: Option<(NaiveDate, NaiveDate)> = None;
+        let mut bucket: Vec<f64> = Vec::new();
+
+        for (date, value) in self.iter() {
+            let (label, end) = period_of(date, frequency)?;
+            if period.map(|(l, _)| l) != Some(label) {
+                if let Some((l, _)) = period {
+                    dates.push(l);
+                    values.push(aggregate(&bucket, method));
+                }
+                period = Some((label, end));
+                bucket.clear();
+            }
+            bucket.extend(value);
+        }
+        if let Some((label, end)) = period {
+            let last = *self.dates().last().unwrap_or(&label);
+            let complete = match self.frequency() {
+                Some(source) => next_observation(last, &source).is_none_or(|next| next > end),
+                None => true,
+            };
+            if complete || incomplete == Incomplete::Keep {
+                dates.push(label);
+                values.push(aggregate(&bucket, method));
+            }
+        }
+
+        let mut series = self.with_parts(dates, values);
+        if let Some(meta) = &mut series.meta {
+            meta.frequency = frequency.to_string();
+            meta.frequency_short = Some(frequency.short());
+        }
+        Ok(series)
+    }
+}
+
+fn aggregate(values: &[f64], method: Aggregation) -> Option<f64> {
+    if values.is_empty() {
+        return None
+    }
+    match method {
+        Aggregation::Avg => Some(values.iter().sum::<f64>() / values.len() as f64),
+        Aggregation::Sum => Some(values.iter().sum()),
+        Aggregation::Eop => values.last().copied(),
+    }
+}
+
+// The label and last day of the period containing `date`.
+pub(crate) fn period_of(date: NaiveDate, frequency: &Frequency) -> Result<(NaiveDate, NaiveDate)> {
+    let months = |start_month: u32, len: u32| {
+        let start = NaiveDate::from_ymd_opt(date.year(), start_month, 1).unwrap();
+        let end = start + Months::new(len) - Duration::days(1);
+        (start, end)
+    };
+    let period = match frequency {
+        Frequency::Daily => (date, date),
+        Frequency::Weekly => week_ending(date, Weekday::Friday, 1),
+        Frequency::WeeklyEnding(day) => week_ending(date, *day, 1),
+        Frequency::Biweekly => week_ending(date, Weekday::Wednesday, 2),
+        Frequency::BiweeklyEnding(day) => week_ending(date, *day, 2),
+        Frequency::Monthly => months(date.month(), 1),
+        Frequency::Quarterly => months((date.month0() / 3) * 3 + 1, 3),
+        Frequency::Semiannual => months((date.month0() / 6) * 6 + 1, 6),
+        Frequency::Annual => months(1, 12),
+        Frequency::Other(s) => return Err(anyhow!("Cannot resample to frequency [{}].", s)),
+    };
+    Ok(period)
+}
+
+// The period of `weeks` weeks ending on `day` that contains `date`, labelled by its end. Biweekly
+// periods are aligned so that one ends in the first week of 1970.
+fn week_ending(date: NaiveDate, day: Weekday, weeks: i64) -> (NaiveDate, NaiveDate) {
+    let target = chrono_weekday(day).num_days_from_monday() as i64;
+    let current = date.weekday().num_days_from_monday() as i64;
+    let mut end = date + Duration::days((target - current).rem_euclid(7));
+    if weeks == 2 {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let epoch_end = epoch + Duration::days((target - epoch.weekday().num_days_from_monday() as i64).rem_euclid(7));
+        if (end - epoch_end).num_days().rem_euclid(14) != 0 {
+            end += Duration::days(7);
+        }
+    }
+    (end, end)
+}
+
+// The date after `date` at `frequency`. Daily series skip weekends.
+pub(crate) fn next_observation(date: NaiveDate, frequency: &Frequency) -> Option<NaiveDate> {
+    match frequency {
+        Frequency::Daily => {
+            let mut next = date + Duration::days(1);
+            while next.weekday().num_days_from_monday() >= 5 {
+                next += Duration::days(1);
+            }
+            Some(next)
+        },
+        Frequency::Weekly | Frequency::WeeklyEnding(_) => Some(date + Duration::days(7)),
+        Frequency::Biweekly | Frequency::BiweeklyEnding(_) => Some(date + Duration::days(14)),
+        Frequency::Monthly => date.checked_add_months(Months::new(1)),
+        Frequency::Quarterly => date.checked_add_months(Months::new(3)),
+        Frequency::Semiannual => date.checked_add_months(Months::new(6)),
+        Frequency::Annual => date.checked_add_months(Months::new(12)),
+        Frequency::Other(_) => None,
+    }
+}
+
+fn chrono_weekday(day: Weekday) -> chrono::Weekday {
+    match day {
+        Weekday::Monday => chrono::Weekday::Mon,
+        Weekday::Tuesday => chrono::Weekday::Tue,
+        Weekday::Wednesday => chrono::Weekday::Wed,
+        Weekday::Thursday => chrono::Weekday::Thu,
+        Weekday::Friday => chrono::Weekday::Fri,
+        Weekday::Saturday => chrono::Weekday::Sat,
+        Weekday::Sunday => chrono::Weekday::Sun,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, NaiveDate};
+    use crate::{Aggregation, Frequency, Incomplete, SeriesItem, TimeSeries, Weekday};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn monthly_to_quarterly() {
+        let ts = TimeSeries::new(
+            (1..=8).map(|m| (date(2020, m, 1), if m == 2 { None } else { Some(m as f64) })).collect()
+        ).unwrap();
+
+        let avg = ts.resample(&Frequency::Quarterly, Aggregation::Avg).unwrap();
+        assert_eq!(avg.dates(), &[date(2020, 1, 1), date(2020, 4, 1)]);
+        assert_eq!(avg.values(), &[Some(2.0), Some(5.0)]);
+
+        let sum = ts.resample_with(&Frequency::Quarterly, Aggregation::Sum, Incomplete::Keep).unwrap();
+        assert_eq!(sum.values(), &[Some(4.0), Some(15.0), Some(15.0)]);
+
+        let eop = ts.resample(&Frequency::Annual, Aggregation::Eop).unwrap();
+        assert!(eop.is_empty());
+        let eop = ts.resample_with(&Frequency::Annual, Aggregation::Eop, Incomplete::Keep).unwrap();
+        assert_eq!(eop.values(), &[Some(8.0)]);
+    }
+
+    #[test]
+    fn daily_to_weekly_ending() {
+        // Business days from Monday 2024-01-01 to Wednesday 2024-01-17.
+        let ts = TimeSeries::new(
+            (0..17)
+                .map(|i| date(2024, 1, 1) + Duration::days(i))
+                .filter(|d| chrono::Datelike::weekday(d).num_days_from_monday() < 5)
+                .map(|d| (d, Some(chrono::Datelike::day(&d) as f64)))
+                .collect()
+        ).unwrap();
+
+        let fri = ts.resample_with(&Frequency::Weekly, Aggregation::Eop, Incomplete::Keep).unwrap();
+        assert_eq!(fri.dates(), &[date(2024, 1, 5), date(2024, 1, 12), date(2024, 1, 19)]);
+        assert_eq!(fri.values(), &[Some(5.0), Some(12.0), Some(17.0)]);
+        assert_eq!(ts.resample(&Frequency::Weekly, Aggregation::Eop).unwrap().len(), 2);
+
+        let wed = ts.resample(&Frequency::WeeklyEnding(Weekday::Wednesday), Aggregation::Avg).unwrap();
+        assert_eq!(wed.dates(), &[date(2024, 1, 3), date(2024, 1, 10), date(2024, 1, 17)]);
+        assert_eq!(wed.values()[1], Some((4.0 + 5.0 + 8.0 + 9.0 + 10.0) / 5.0));
+    }
+
+    #[test]
+    fn resampling_to_a_higher_frequency_fails() {
+        let meta = SeriesItem { frequency: "Quarterly".into(), ..crate::test::series_item("GDP") };
+        let ts = TimeSeries::new((0..4).map(|q| (date(2020, q * 3 + 1, 1), Some(q as f64))).collect())
+            .unwrap()
+            .with_meta(meta);
+
+        assert!(ts.resample(&Frequency::Monthly, Aggregation::Avg).is_err());
+        assert_eq!(ts.resample(&Frequency::Quarterly, Aggregation::Avg).unwrap().len(), 4);
+        assert_eq!(ts.resample(&Frequency::Annual, Aggregation::Avg).unwrap().values(), &[Some(1.5)]);
+    }
+}
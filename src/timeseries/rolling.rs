@@ -0,0 +1,373 @@
+//! Rolling-window and expanding statistics. The window ending at each date holds the values of
+//! the series within a number of periods, a calendar duration, or since the start. Missing values
+//! are skipped, and a statistic is missing where the window holds fewer values than the minimum.
+//!
+//! ```
+//! let unrate = FredClient::time_series(&SeriesId::new("UNRATE")?)?;
+//! let mean = unrate.rolling(Window::Months(12)).min_observations(10).mean();
+//! let z = unrate.expanding().min_observations(24).zscore();
+//! ```
+
+use chrono::{Duration, Months, NaiveDate};
+use std::collections::VecDeque;
+use crate::TimeSeries;
+
+/// The extent of a window ending at a date.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// The date and the dates of the `n - 1` periods before it.
+    Periods(usize),
+    /// Dates less than `n` days before the date.
+    Days(i64),
+    /// Dates less than `n` months before the date.
+    Months(u32),
+    /// Every date up to and including the date.
+    Expanding,
+}
+
+/// Statistics over a window, from `TimeSeries::rolling` or `TimeSeries::expanding`.
+#[derive(Clone, Copy, Debug)]
+pub struct Rolling<'a> {
+    series:                         &'a TimeSeries,
+    window:                         Window,
+    min_observations:               usize,
+}
+
+impl TimeSeries {
+    /// Statistics over `window`. The minimum number of values defaults to `n` for
+    /// `Window::Periods(n)` and to one otherwise.
+    pub fn rolling(&self, window: Window) -> Rolling<'_> {
+        let min_observations = match window {
+            Window::Periods(n) => n.max(1),
+            _ => 1,
+        };
+        Rolling { series: self, window, min_observations }
+    }
+
+    /// Statistics over every date up to each date.
+    pub fn expanding(&self) -> Rolling<'_> {
+        self.rolling(Window::Expanding)
+    }
+}
+
+impl<'a> Rolling<'a> {
+    /// The minimum number of values in a window for the statistic to be calculated.
+    pub fn min_observations(mut self, n: usize) -> Self {
+        self.min_observations = n.max(1);
+        self
+    }
+
+    /// The number of values in each window.
+    pub fn count(&self) -> TimeSeries {
+        self.moments(|_, m| Some(m.n as f64))
+    }
+
+    pub fn mean(&self) -> TimeSeries {
+        self.moments(|_, m| Some(m.mean))
+    }
+
+    /// The sample standard deviation, missing for a window with a single value.
+    pub fn std(&self) -> TimeSeries {
+        self.moments(|_, m| m.std())
+    }
+
+    pub fn min(&self) -> TimeSeries {
+        self.extreme(|a, b| a < b)
+    }
+
+    pub fn max(&self) -> TimeSeries {
+        self.extreme(|a, b| a > b)
+    }
+
+    /// The value less the mean of its window, in standard deviations of the window.
+    pub fn zscore(&self) -> TimeSeries {
+        self.moments(|x, m| Some((x? - m.mean) / m.std().filter(|sd| *sd > 0.0)?))
+    }
+
+    /// The correlation with `other` over each window, using the dates where both have values.
+    /// `other` is aligned to the dates of this series. The result has no metadata.
+    pub fn corr(&self, other: &TimeSeries) -> TimeSeries {
+        let dates = self.series.dates();
+        let ys = other.reindex(dates);
+        let co = CoMoments { xs: self.series.values(), ys: ys.values(), ..CoMoments::default() };
+        let values = self.slide(co, |_, c| {
+            if c.n < self.min_observations.max(2) {
+                return None
+            }
+            Some(c.cxy / (c.sxx * c.syy).sqrt()).filter(|r| r.is_finite())
+        });
+        TimeSeries::from_parts(dates.to_vec(), values, None)
+    }
+
+    // A statistic of the moments of each window with enough values, given the value at the end of
+    // the window.
+    fn moments<F: Fn(Option<f64>, &Moments) -> Option<f64>>(&self, statistic: F) -> TimeSeries {
+        let values = self.series.values();
+        let moments = Moments { values, ..Moments::default() };
+        let stats = self.slide(moments, |i, m| {
+            if m.n < self.min_observations {
+                return None
+            }
+            statistic(values[i], m)
+        });
+        self.series.with_parts(self.series.dates().to_vec(), stats)
+    }
+
+    // The least value of each window with enough values, where `a` is less than `b` if
+    // `before(a, b)`.
+    fn extreme(&self, before: fn(f64, f64) -> bool) -> TimeSeries {
+        let extreme = Extreme { values: self.series.values(), before, candidates: VecDeque::new(), n: 0 };
+        let stats = self.slide(extreme, |_, e| {
+            if e.n < self.min_observations {
+                return None
+            }
+            e.candidates.front().and_then(|k| e.values[*k])
+        });
+        self.series.with_parts(self.series.dates().to_vec(), stats)
+    }
+
+    // Move a window along the series, adding each position as the window reaches it and removing
+    // it as the window passes it, and apply `statistic` at each position. This is a single pass
+    // because the start of the window never moves back.
+    fn slide<A, F>(&self, mut acc: A, statistic: F) -> Vec<Option<f64>>
+    where
+        A: Accumulator,
+        F: Fn(usize, &A) -> Option<f64>,
+    {
+        let mut first = 0;
+        (0..self.series.len())
+            .map(|i| {
+                let start = self.start(i);
+                for k in first..start {
+                    acc.remove(k);
+                }
+                first = start;
+                acc.add(i);
+                statistic(i, &acc)
+            })
+            .collect()
+    }
+
+    // The position of the first date in the window ending at position `i`.
+    fn start(&self, i: usize) -> usize {
+        let dates = self.series.dates();
+        let after = |earliest: Option<NaiveDate>| match earliest {
+            Some(earliest) => dates[..=i].partition_point(|date| *date <= earliest),
+            None => 0,
+        };
+        match self.window {
+            Window::Periods(n) => (i + 1).saturating_sub(n),
+            Window::Days(n) => after(dates[i].checked_sub_signed(Duration::days(n))),
+            Window::Months(n) => after(dates[i].checked_sub_months(Months::new(n))),
+            Window::Expanding => 0,
+        }
+    }
+}
+
+// State of a window that positions are added to and removed from. Missing values are skipped.
+trait Accumulator {
+    fn add(&mut self, k: usize);
+    fn remove(&mut self, k: usize);
+}
+
+// The number, mean and sum of squared deviations of the values in a window, by Welford's method.
+#[derive(Default)]
+struct Moments<'a> {
+    values:                         &'a [Option<f64>],
+    n:                              usize,
+    mean:                           f64,
+    m2:                             f64,
+}
+
+impl Moments<'_> {
+    // The sample standard deviation.
+    fn std(&self) -> Option<f64> {
+        if self.n < 2 {
+            return None
+        }
+        Some((self.m2.max(0.0) / (self.n - 1) as f64).sqrt())
+    }
+}
+
+impl Accumulator for Moments<'_> {
+    fn add(&mut self, k: usize) {
+        if let Some(x) = self.values[k] {
+            self.n += 1;
+            let delta = x - self.mean;
+            self.mean += delta / self.n as f64;
+            self.m2 += delta * (x - self.mean);
+        }
+    }
+
+    fn remove(&mut self, k: usize) {
+        if let Some(x) = self.values[k] {
+            if self.n == 1 {
+                *self = Moments { values: self.values, ..Moments::default() };
+                return
+            }
+            self.n -= 1;
+            let delta = x - self.mean;
+            self.mean -= delta / self.n as f64;
+            self.m2 -= delta * (x - self.mean);
+        }
+    }
+}
+
+// The co-moments of the pairs of values in a window where both are present.
+#[derive(Default)]
+struct CoMoments<'a> {
+    xs:                             &'a [Option<f64>],
+    ys:                             &'a [Option<f64>],
+    n:                              usize,
+    mx:                             f64,
+    my:                             f64,
+    cxy:                            f64,
+    sxx:                            f64,
+    syy:                            f64,
+}
+
+impl Accumulator for CoMoments<'_> {
+    fn add(&mut self, k: usize) {
+        if let (Some(x), Some(y)) = (self.xs[k], self.ys[k]) {
+            self.n += 1;
+            let (dx, dy) = (x - self.mx, y - self.my);
+            self.mx += dx / self.n as f64;
+            self.my += dy / self.n as f64;
+            self.cxy += dx * (y - self.my);
+            self.sxx += dx * (x - self.mx);
+            self.syy += dy * (y - self.my);
+        }
+    }
+
+    fn remove(&mut self, k: usize) {
+        if let (Some(x), Some(y)) = (self.xs[k], self.ys[k]) {
+            if self.n == 1 {
+                *self = CoMoments { xs: self.xs, ys: self.ys, ..CoMoments::default() };
+                return
+            }
+            self.n -= 1;
+            let (dx, dy) = (x - self.mx, y - self.my);
+            self.mx -= dx / self.n as f64;
+            self.my -= dy / self.n as f64;
+            self.cxy -= dx * (y - self.my);
+            self.sxx -= dx * (x - self.mx);
+            self.syy -= dy * (y - self.my);
+        }
+    }
+}
+
+// The positions in a window that may yet hold its least value, in order of position and of
+// value, so that the front holds the least value.
+struct Extreme<'a> {
+    values:                         &'a [Option<f64>],
+    before:                         fn(f64, f64) -> bool,
+    candidates:                     VecDeque<usize>,
+    n:                              usize,
+}
+
+impl Accumulator for Extreme<'_> {
+    fn add(&mut self, k: usize) {
+        if let Some(x) = self.values[k] {
+            while let Some(last) = self.candidates.back().and_then(|j| self.values[*j]) {
+                if (self.before)(last, x) {
+                    break
+                }
+                self.candidates.pop_back();
+            }
+            self.candidates.push_back(k);
+            self.n += 1;
+        }
+    }
+
+    fn remove(&mut self, k: usize) {
+        if self.values[k].is_some() {
+            if self.candidates.front() == Some(&k) {
+                self.candidates.pop_front();
+            }
+            self.n -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use crate::{TimeSeries, Window};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn monthly(values: &[Option<f64>]) -> TimeSeries {
+        TimeSeries::new(
+            values.iter().enumerate().map(|(i, v)| (date(2020, i as u32 + 1, 1), *v)).collect()
+        ).unwrap()
+    }
+
+    #[test]
+    fn rolling_statistics_skip_missing_values() {
+        let ts = monthly(&[Some(1.0), Some(2.0), None, Some(4.0), Some(5.0), Some(9.0)]);
+
+        let mean = ts.rolling(Window::Periods(3)).mean();
+        assert_eq!(mean.values(), &[None, None, None, None, None, Some(6.0)]);
+        let mean = ts.rolling(Window::Periods(3)).min_observations(2).mean();
+        assert_eq!(mean.values(), &[None, Some(1.5), Some(1.5), Some(3.0), Some(4.5), Some(6.0)]);
+
+        // The three months to April 1 are February to April.
+        let max = ts.rolling(Window::Months(3)).max();
+        assert_eq!(max.values()[3], Some(4.0));
+        assert_eq!(ts.rolling(Window::Months(3)).count().values()[3], Some(2.0));
+        assert_eq!(
+            ts.rolling(Window::Days(28)).count().values(),
+            &[Some(1.0), Some(1.0), None, Some(1.0), Some(1.0), Some(1.0)],
+        );
+
+        assert_eq!(ts.expanding().min().values()[5], Some(1.0));
+        assert_eq!(ts.expanding().std().values()[1], Some(0.5f64.sqrt()));
+        assert_eq!(ts.expanding().std().values()[0], None);
+
+        // The window of June holds 4, 5 and 9, with mean 6 and standard deviation sqrt(7).
+        let z = ts.rolling(Window::Periods(3)).zscore();
+        assert!((z.values()[5].unwrap() - 3.0 / 7f64.sqrt()).abs() < 1e-12);
+        assert_eq!(z.values()[2], None);
+    }
+
+    #[test]
+    fn rolling_correlation() {
+        let x = monthly(&[Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0)]);
+        let y = monthly(&[Some(2.0), Some(4.0), None, Some(8.0), Some(4.0)]);
+
+        let r = x.rolling(Window::Periods(3)).min_observations(2).corr(&y);
+        assert_eq!(r.values()[0], None);
+        assert!((r.values()[1].unwrap() - 1.0).abs() < 1e-12);
+        assert!((r.values()[3].unwrap() - 1.0).abs() < 1e-12);
+        assert!((r.values()[4].unwrap() + 1.0).abs() < 1e-12);
+        assert!(r.meta.is_none());
+    }
+
+    #[test]
+    fn sliding_statistics_match_each_window() {
+        let values: Vec<Option<f64>> = (0..11)
+            .map(|i| if i % 4 == 2 { None } else { Some(((i * 7) % 5) as f64 + 0.25 * i as f64) })
+            .collect();
+        let ts = monthly(&values);
+        let rolling = ts.rolling(Window::Months(4)).min_observations(2);
+        let (mean, std, min, max) = (rolling.mean(), rolling.std(), rolling.min(), rolling.max());
+
+        for i in 0..values.len() {
+            // The four months to the first of month `i` are months `i - 3` to `i`.
+            let window: Vec<f64> = values[i.saturating_sub(3)..=i].iter().flatten().copied().collect();
+            if window.len() < 2 {
+                assert_eq!(mean.values()[i], None);
+                continue
+            }
+            let n = window.len() as f64;
+            let m = window.iter().sum::<f64>() / n;
+            let sd = (window.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+            assert!((mean.values()[i].unwrap() - m).abs() < 1e-12);
+            assert!((std.values()[i].unwrap() - sd).abs() < 1e-12);
+            assert_eq!(min.values()[i], window.iter().copied().reduce(f64::min));
+            assert_eq!(max.values()[i], window.iter().copied().reduce(f64::max));
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Local versions of the transformations FRED applies with the `units` request parameter. With
+//! `x(t)` the value at `t` and `n` the observations per year,
+//!
+//! | code  | value                               |
+//! |-------|-------------------------------------|
+//! | `chg` | `x(t) - x(t-1)`                     |
+//! | `ch1` | `x(t) - x(t-n)`                     |
+//! | `pch` | `(x(t) / x(t-1) - 1) * 100`         |
+//! | `pc1` | `(x(t) / x(t-n) - 1) * 100`         |
+//! | `pca` | `((x(t) / x(t-1))^n - 1) * 100`     |
+//! | `cch` | `(ln x(t) - ln x(t-1)) * 100`       |
+//! | `cca` | `(ln x(t) - ln x(t-1)) * 100 * n`   |
+//! | `log` | `ln x(t)`                           |
+//!
+//! For daily series the year-ago value is the value on the same date a year earlier.
+
+use anyhow::{anyhow, Result};
+use chrono::Months;
+use std::{fmt, str::FromStr};
+use crate::{Frequency, TimeSeries};
+
+/// A value of the FRED `units` request parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transformation {
+    Lin,
+    Chg,
+    Ch1,
+    Pch,
+    Pc1,
+    Pca,
+    Cch,
+    Cca,
+    Log,
+}
+
+impl Transformation {
+    /// The value of the FRED `units` request parameter.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Transformation::Lin => "lin",
+            Transformation::Chg => "chg",
+            Transformation::Ch1 => "ch1",
+            Transformation::Pch => "pch",
+            Transformation::Pc1 => "pc1",
+            Transformation::Pca => "pca",
+            Transformation::Cch => "cch",
+            Transformation::Cca => "cca",
+            Transformation::Log => "log",
+        }
+    }
+
+    /// The name FRED gives the transformation.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Transformation::Lin => "Levels",
+            Transformation::Chg => "Change",
+            Transformation::Ch1 => "Change from Year Ago",
+            Transformation::Pch => "Percent Change",
+            Transformation::Pc1 => "Percent Change from Year Ago",
+            Transformation::Pca => "Compounded Annual Rate of Change",
+            Transformation::Cch => "Continuously Compounded Rate of Change",
+            Transformation::Cca => "Continuously Compounded Annual Rate of Change",
+            Transformation::Log => "Natural Log",
+        }
+    }
+
+    /// The abbreviated name, as FRED writes it in short units.
+    pub fn short_description(&self) -> &'static str {
+        match self {
+            Transformation::Lin => "Lvl.",
+            Transformation::Chg => "Chg.",
+            Transformation::Ch1 => "Chg. from Yr. Ago",
+            Transformation::Pch => "% Chg.",
+            Transformation::Pc1 => "% Chg. from Yr. Ago",
+            Transformation::Pca => "Cmpd. Annual Rate of Chg.",
+            Transformation::Cch => "Cont. Cmpd. Rate of Chg.",
+            Transformation::Cca => "Cont. Cmpd. Annual Rate of Chg.",
+            Transformation::Log => "Nat. Log",
+        }
+    }
+
+    /// The units of a series in `units` after the transformation.
+    pub fn units(&self, units: &str) -> String {
+        self.transformed_units(units, self.description())
+    }
+
+    /// The short units of a series in `units_short` after the transformation.
+    pub fn units_short(&self, units_short: &str) -> String {
+        self.transformed_units(units_short, self.short_description())
+    }
+
+    fn transformed_units(&self, units: &str, description: &str) -> String {
+        match self {
+            Transformation::Lin => units.to_string(),
+            Transformation::Chg | Transformation::Ch1 => format!("{}, {}", description, units),
+            Transformation::Log => format!("{} of {}", description, units),
+            _ => description.to_string(),
+        }
+    }
+}
+
+impl FromStr for Transformation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "lin" => Ok(Transformation::Lin),
+            "chg" => Ok(Transformation::Chg),
+            "ch1" => Ok(Transformation::Ch1),
+            "pch" => Ok(Transformation::Pch),
+            "pc1" => Ok(Transformation::Pc1),
+            "pca" => Ok(Transformation::Pca),
+            "cch" => Ok(Transformation::Cch),
+            "cca" => Ok(Transformation::Cca),
+            "log" => Ok(Transformation::Log),
+            _ => Err(anyhow!("Unknown transformation [{}].", s)),
+        }
+    }
+}
+
+impl fmt::Display for Transformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl TimeSeries {
+    /// Apply `transformation` using the frequency of the series. Fails if the transformation
+    /// needs a frequency and none is known.
+    pub fn transform(&self, transformation: Transformation) -> Result<TimeSeries> {
+        match transformation {
+            Transformation::Ch1 | Transformation::Pc1 | Transformation::Pca | Transformation::Cca => {
+                let frequency = self
+                    .frequency()
+                    .ok_or_else(|| anyhow!("[{}] needs the frequency of the series.", transformation))?;
+                self.transform_with_frequency(transformation, &frequency)
+            },
+            _ => self.transform_with_frequency(transformation, &Frequency::Annual),
+        }
+    }
+
+    /// Apply `transformation`, annualizing as for `frequency`. A value is missing if a value it
+    /// depends on is missing or the result is not finite.
+    pub fn transform_with_frequency(&self, transformation: Transformation, frequency: &Frequency) -> Result<TimeSeries> {
+        let n = frequency
+            .per_year()
+            .ok_or_else(|| anyhow!("Frequency [{}] has no annualization.", frequency))?;
+        let values = self.values();
+        let previous = |i: usize| if i == 0 { None } else { values[i - 1] };
+        let year_ago = |i: usize| -> Option<f64> {
+            if *frequency == Frequency::Daily {
+                let date = self.dates()[i].checked_sub_months(Months::new(12))?;
+                self.get(date)
+            } else {
+                i.checked_sub(n as usize).and_then(|j| values[j])
+            }
+        };
+        let n = n as f64;
+
+        let transformed = (0..self.len())
+            .map(|i| {
+                let x = values[i]?;
+                let value = match transformation {
+                    Transformation::Lin => x,
+                    Transformation::Chg => x - previous(i)?,
+                    Transformation::Ch1 => x - year_ago(i)?,
+                    Transformation::Pch => (x / previous(i)? - 1.0) * 100.0,
+                    Transformation::Pc1 => (x / year_ago(i)? - 1.0) * 100.0,
+                    Transformation::Pca => ((x / previous(i)?).powf(n) - 1.0) * 100.0,
+                    Transformation::Cch => (x.ln() - previous(i)?.ln()) * 100.0,
+                    Transformation::Cca => (x.ln() - previous(i)?.ln()) * 100.0 * n,
+                    Transformation::Log => x.ln(),
+                };
+                Some(value).filter(|value| value.is_finite())
+            })
+            .collect();
+
+        let mut series = self.with_parts(self.dates().to_vec(), transformed);
+        if let Some(meta) = &mut series.meta {
+            meta.units = transformation.units(&meta.units);
+            meta.units_short = transformation.units_short(&meta.units_short);
+        }
+        Ok(series)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Months, NaiveDate};
+    use crate::{Frequency, SeriesItem, TimeSeries, Transformation};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    // Two years of a series doubling every year, observed `per_year` times a year.
+    fn doubling(frequency: &Frequency, dates: impl Fn(usize) -> NaiveDate) -> TimeSeries {
+        let per_year = frequency.per_year().unwrap() as usize;
+        let meta = SeriesItem { frequency: frequency.to_string(), ..crate::test::series_item("X") };
+        TimeSeries::new(
+            (0..=2 * per_year).map(|i| (dates(i), Some(100.0 * 2f64.powf(i as f64 / per_year as f64)))).collect()
+        ).unwrap().with_meta(meta)
+    }
+
+    fn assert_all(ts: &TimeSeries, skip: usize, expected: f64) {
+        assert!(ts.values()[..skip].iter().all(|v| v.is_none()));
+        for value in &ts.values()[skip..] {
+            assert!((value.unwrap() - expected).abs() < 1e-9, "{:?} != {}", value, expected);
+        }
+    }
+
+    #[test]
+    fn annualized_rates_of_a_series_doubling_every_year() {
+        let series = [
+            doubling(&Frequency::Quarterly, |i| date(2018, 1, 1) + Months::new(3 * i as u32)),
+            doubling(&Frequency::Monthly, |i| date(2018, 1, 1) + Months::new(i as u32)),
+            doubling(&Frequency::Weekly, |i| date(2018, 1, 5) + Duration::weeks(i as i64)),
+        ];
+        for ts in &series {
+            let n = ts.frequency().unwrap().per_year().unwrap() as usize;
+            assert_all(&ts.transform(Transformation::Pca).unwrap(), 1, 100.0);
+            assert_all(&ts.transform(Transformation::Cca).unwrap(), 1, 100.0 * 2f64.ln());
+            assert_all(&ts.transform(Transformation::Cch).unwrap(), 1, 100.0 * 2f64.ln() / n as f64);
+            assert_all(&ts.transform(Transformation::Pch).unwrap(), 1, 100.0 * (2f64.powf(1.0 / n as f64) - 1.0));
+            assert_all(&ts.transform(Transformation::Pc1).unwrap(), n, 100.0);
+            let ch1 = ts.transform(Transformation::Ch1).unwrap();
+            assert!((ch1.values()[2 * n].unwrap() - 200.0).abs() < 1e-9);
+        }
+
+        let meta = series[0].transform(Transformation::Pc1).unwrap().meta.unwrap();
+        assert_eq!(meta.units, "Percent Change from Year Ago");
+        assert_eq!(meta.units_short, "% Chg. from Yr. Ago");
+        let meta = series[0].transform(Transformation::Ch1).unwrap().meta.unwrap();
+        assert_eq!(meta.units_short, "Chg. from Yr. Ago, %");
+    }
+
+    #[test]
+    fn missing_values_propagate() {
+        let ts = TimeSeries::new(
+            (1..=6).map(|m| (date(2020, m, 1), if m == 3 { None } else { Some(m as f64) })).collect()
+        ).unwrap();
+        let chg = ts.transform(Transformation::Chg).unwrap();
+        assert_eq!(chg.values(), &[None, Some(1.0), None, None, Some(1.0), Some(1.0)]);
+        let log = ts.transform(Transformation::Log).unwrap();
+        assert_eq!(log.values()[2], None);
+        assert_eq!(log.values()[3], Some(4f64.ln()));
+        let ch1 = ts.transform_with_frequency(Transformation::Ch1, &Frequency::Quarterly).unwrap();
+        assert_eq!(ch1.values(), &[None, None, None, None, Some(4.0), Some(4.0)]);
+        let ch1 = ts.transform_with_frequency(Transformation::Ch1, &Frequency::Semiannual).unwrap();
+        assert_eq!(ch1.values(), &[None, None, None, Some(2.0), None, Some(2.0)]);
+        let zero = TimeSeries::new(vec![(date(2020, 1, 1), Some(0.0)), (date(2020, 2, 1), Some(1.0))]).unwrap();
+        assert_eq!(zero.transform(Transformation::Pch).unwrap().values(), &[None, None]);
+    }
+
+    #[test]
+    fn daily_year_ago_uses_calendar_date() {
+        let ts = TimeSeries::new(vec![
+            (date(2019, 3, 1), Some(100.0)),
+            (date(2019, 3, 4), Some(101.0)),
+            (date(2020, 3, 2), Some(105.0)),
+            (date(2020, 3, 4), Some(110.0)),
+        ]).unwrap();
+        let pc1 = ts.transform_with_frequency(Transformation::Pc1, &Frequency::Daily).unwrap();
+        assert_eq!(pc1.values()[2], None);
+        assert!((pc1.values()[3].unwrap() - 100.0 * (110.0 / 101.0 - 1.0)).abs() < 1e-12);
+    }
+}
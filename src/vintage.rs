@@ -0,0 +1,376 @@
+//! Observations by vintage, returned by `series/observations` when `output_type` is 2, 3 or 4.
+//! Each observation is keyed by a column per vintage, such as `GDP_20090130`, instead of a single
+//! `value`.
+
+use anyhow::{anyhow, Result};
+use serde::{
+    de::Deserializer,
+    ser::{SerializeMap, SerializeSeq, Serializer},
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+/// The `output_type` of a vintage request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VintageOutput {
+    /// Observations by vintage date, all observations (output_type 2).
+    AllObservations,
+    /// Observations by vintage date, new and revised observations only (output_type 3).
+    NewAndRevised,
+    /// Observations, initial release only (output_type 4).
+    InitialRelease,
+}
+
+impl VintageOutput {
+    /// The value of the `output_type` request parameter.
+    pub fn code(&self) -> usize {
+        match self {
+            VintageOutput::AllObservations => 2,
+            VintageOutput::NewAndRevised => 3,
+            VintageOutput::InitialRelease => 4,
+        }
+    }
+}
+
+/// See [Fred docs: /fred/series/observations](https://fred.stlouisfed.org/docs/api/fred/series_observations.html).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct VintageObservations {
+    pub realtime_start:             String,
+    pub realtime_end:               String,
+    pub observation_start:          String,
+    pub observation_end:            String,
+    pub units:                      String,
+    pub output_type:                isize,
+    pub file_type:                  String,
+    pub order_by:                   String,
+    pub sort_order:                 String,
+    pub count:                      isize,
+    pub offset:                     isize,
+    pub limit:                      isize,
+    pub observations:               VintageMatrix,
+}
+
+/// A column of a `VintageMatrix`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VintageColumn {
+    /// The column name as returned by FRED, for example `GDP_20090130`.
+    pub name:                       String,
+    /// The vintage date in `YYYY-MM-DD` form, for example `2009-01-30`.
+    pub vintage_date:               String,
+}
+
+impl VintageColumn {
+    fn from_name(name: &str) -> Result<Self> {
+        let suffix = name
+            .rsplit('_')
+            .next()
+            .filter(|s| s.len() == 8 && s.chars().all(|c| c.is_ascii_digit()))
+            .ok_or_else(|| anyhow!("Expected vintage column [{}] to end in _YYYYMMDD.", name))?;
+        Ok(
+            VintageColumn {
+                name:           name.to_string(),
+                vintage_date:   format!("{}-{}-{}", &suffix[0..4], &suffix[4..6], &suffix[6..8]),
+            }
+        )
+    }
+}
+
+/// Observation dates by vintages. A cell is `None` when the vintage has no value for that date,
+/// which happens when the date had not yet been observed, or with output_type 3 and 4 when the
+/// value was unchanged. Missing values FRED reports as `"."` are kept as `Some(".")`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VintageMatrix {
+    dates:      Vec<String>,
+    columns:    Vec<VintageColumn>,
+    // values[row][column]
+    values:     Vec<Vec<Option<String>>>,
+}
+
+impl VintageMatrix {
+    fn from_rows(rows: Vec<BTreeMap<String, Option<String>>>) -> Result<Self> {
+        let mut names: Vec<String> = Vec::new();
+        for row in &rows {
+            for key in row.keys() {
+                if key != "date" && !names.contains(key) {
+                    names.push(key.clone());
+                }
+            }
+        }
+        let mut columns = names
+            .iter()
+            .map(|name| VintageColumn::from_name(name))
+            .collect::<Result<Vec<VintageColumn>>>()?;
+        columns.sort_by(|a, b| a.vintage_date.cmp(&b.vintage_date).then(a.name.cmp(&b.name)));
+
+        let mut dates = Vec::new();
+        let mut values = Vec::new();
+        for mut row in rows {
+            let date = row
+                .remove("date")
+                .flatten()
+                .ok_or_else(|| anyhow!("Vintage observation is missing a date."))?;
+            dates.push(date);
+            values.push(
+                columns.iter().map(|col| row.remove(&col.name).flatten()).collect()
+            );
+        }
+        Ok(VintageMatrix { dates, columns, values })
+    }
+
+    fn into_rows(self) -> Vec<BTreeMap<String, Option<String>>> {
+        let columns = self.columns;
+        self.dates
+            .into_iter()
+            .zip(self.values)
+            .map(|(date, row)| {
+                let mut map: BTreeMap<String, Option<String>> = columns
+                    .iter()
+                    .zip(row)
+                    .filter(|(_, v)| v.is_some())
+                    .map(|(col, v)| (col.name.clone(), v))
+                    .collect();
+                map.insert("date".into(), Some(date));
+                map
+            })
+            .collect()
+    }
+
+    /// Combine two matrices, for example from requests for different sets of vintage dates.
+    /// Where both have a value for the same date and column, `other` wins.
+    pub fn merge(self, other: VintageMatrix) -> VintageMatrix {
+        let mut by_date: BTreeMap<String, BTreeMap<String, Option<String>>> = BTreeMap::new();
+        for row in self.into_rows().into_iter().chain(other.into_rows()) {
+            let date = row.get("date").cloned().flatten().unwrap_or_default();
+            by_date.entry(date).or_default().extend(row);
+        }
+        // Rows and columns were built from valid matrices, so this cannot fail.
+        VintageMatrix::from_rows(by_date.into_values().collect())
+            .expect("merged vintage matrix")
+    }
+
+    /// A matrix with no dates and no vintages.
+    pub fn empty() -> VintageMatrix {
+        VintageMatrix { dates: Vec::new(), columns: Vec::new(), values: Vec::new() }
+    }
+
+    /// Observation dates, in the order returned.
+    pub fn dates(&self) -> &[String] {
+        &self.dates
+    }
+
+    /// Vintage columns, in vintage date order.
+    pub fn columns(&self) -> &[VintageColumn] {
+        &self.columns
+    }
+
+    /// Vintage dates in `YYYY-MM-DD` form.
+    pub fn vintage_dates(&self) -> Vec<String> {
+        self.columns.iter().map(|col| col.vintage_date.clone()).collect()
+    }
+
+    /// The value of observation `date` in vintage `vintage_date`.
+    pub fn value(&self, date: &str, vintage_date: &str) -> Option<&str> {
+        let row = self.dates.iter().position(|d| d == date)?;
+        let col = self.columns.iter().position(|c| c.vintage_date == vintage_date)?;
+        self.values[row][col].as_deref()
+    }
+
+    /// All observations as known in vintage `vintage_date`, as `(date, value)` pairs. Dates with no
+    /// value in that vintage are skipped.
+    pub fn column(&self, vintage_date: &str) -> Option<Vec<(&str, &str)>> {
+        let col = self.columns.iter().position(|c| c.vintage_date == vintage_date)?;
+        Some(
+            self.dates
+                .iter()
+                .zip(&self.values)
+                .filter_map(|(date, row)| row[col].as_deref().map(|v| (date.as_str(), v)))
+                .collect()
+        )
+    }
+
+    /// All vintages of observation `date`, as `(vintage_date, value)` pairs. Vintages with no
+    /// value for that date are skipped.
+    pub fn row(&self, date: &str) -> Option<Vec<(&str, &str)>> {
+        let row = self.dates.iter().position(|d| d == date)?;
+        Some(
+            self.columns
+                .iter()
+                .zip(&self.values[row])
+                .filter_map(|(col, v)| v.as_deref().map(|v| (col.vintage_date.as_str(), v)))
+                .collect()
+        )
+    }
+
+    /// The observations as they were known on `date`, as `(date, value)` pairs. For each
+    /// observation date this is the latest value in a vintage on or before `date`, so it works
+    /// for both output_type 2 and 3. Missing values (`"."`) are skipped.
+    pub fn as_of(&self, date: &str) -> Vec<(&str, &str)> {
+        self.dates
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(obs_date, row)| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .filter(|(col, _)| col.vintage_date.as_str() <= date)
+                    .filter_map(|(_, v)| present(v))
+                    .next_back()
+                    .map(|v| (obs_date.as_str(), v))
+            })
+            .collect()
+    }
+
+    /// The first value published for each observation date, as `(date, vintage_date, value)`.
+    /// Missing values (`"."`), which fill the vintages before an observation was published, are
+    /// skipped.
+    pub fn initial_release(&self) -> Vec<(&str, &str, &str)> {
+        self.dates
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(date, row)| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .find_map(|(col, v)| present(v).map(|v| (col.vintage_date.as_str(), v)))
+                    .map(|(vintage, v)| (date.as_str(), vintage, v))
+            })
+            .collect()
+    }
+
+    /// The number of observation dates.
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+}
+
+// The value of a cell, unless it is empty or missing.
+fn present(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| *v != ".")
+}
+
+impl<'de> Deserialize<'de> for VintageMatrix {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let rows = Vec::<BTreeMap<String, Option<String>>>::deserialize(deserializer)?;
+        VintageMatrix::from_rows(rows).map_err(serde::de::Error::custom)
+    }
+}
+
+// Serializes back to the layout FRED returns, one map per observation date.
+impl Serialize for VintageMatrix {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.dates.len()))?;
+        for (date, row) in self.dates.iter().zip(&self.values) {
+            seq.serialize_element(&VintageRow { date, columns: &self.columns, values: row })?;
+        }
+        seq.end()
+    }
+}
+
+struct VintageRow<'a> {
+    date:       &'a str,
+    columns:    &'a [VintageColumn],
+    values:     &'a [Option<String>],
+}
+
+impl<'a> Serialize for VintageRow<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("date", self.date)?;
+        for (col, value) in self.columns.iter().zip(self.values) {
+            if let Some(value) = value {
+                map.serialize_entry(&col.name, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl fmt::Display for VintageMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::from("date");
+        for col in &self.columns {
+            s.push_str(", ");
+            s.push_str(&col.vintage_date);
+        }
+        for (date, row) in self.dates.iter().zip(&self.values) {
+            s.push('\n');
+            s.push_str(date);
+            for value in row {
+                s.push_str(", ");
+                s.push_str(value.as_deref().unwrap_or(""));
+            }
+        }
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{VintageMatrix, VintageObservations};
+
+    const GDP: &str = r#"[
+        {"date": "2008-07-01", "GDP_20081030": "14546.7", "GDP_20081126": "14420.3"},
+        {"date": "2008-10-01", "GDP_20090130": "14200.3"}
+    ]"#;
+
+    #[test]
+    fn vintage_matrix_parses_sparse_columns() {
+        let matrix: VintageMatrix = serde_json::from_str(GDP).unwrap();
+        assert_eq!(matrix.len(), 2);
+        assert_eq!(matrix.vintage_dates(), vec!["2008-10-30", "2008-11-26", "2009-01-30"]);
+        assert_eq!(matrix.value("2008-07-01", "2008-11-26"), Some("14420.3"));
+        assert_eq!(matrix.value("2008-10-01", "2008-11-26"), None);
+        assert_eq!(
+            matrix.initial_release(),
+            vec![
+                ("2008-07-01", "2008-10-30", "14546.7"),
+                ("2008-10-01", "2009-01-30", "14200.3"),
+            ],
+        );
+    }
+
+    #[test]
+    fn vintage_matrix_as_of_and_merge() {
+        let matrix: VintageMatrix = serde_json::from_str(GDP).unwrap();
+        assert_eq!(matrix.as_of("2008-11-01"), vec![("2008-07-01", "14546.7")]);
+        assert_eq!(
+            matrix.as_of("2009-06-01"),
+            vec![("2008-07-01", "14420.3"), ("2008-10-01", "14200.3")],
+        );
+
+        let later: VintageMatrix = serde_json::from_str(r#"[
+            {"date": "2008-10-01", "GDP_20090227": "14097.2"},
+            {"date": "2009-01-01", "GDP_20090429": "14057.0"}
+        ]"#).unwrap();
+        let merged = matrix.merge(later);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.vintage_dates().len(), 5);
+        assert_eq!(merged.value("2008-10-01", "2009-02-27"), Some("14097.2"));
+        assert_eq!(merged.value("2008-10-01", "2009-01-30"), Some("14200.3"));
+    }
+
+    #[test]
+    fn missing_cells_are_not_releases() {
+        // In output_type 2, vintages before an observation was published hold ".".
+        let obs: VintageObservations =
+            serde_json::from_str(include_str!("../tests/fixtures/series_observations_vintage.json")).unwrap();
+        let matrix = obs.observations;
+        assert_eq!(matrix.value("2008-10-01", "2008-10-30"), Some("."));
+        assert_eq!(matrix.initial_release()[1], ("2008-10-01", "2009-01-30", "14200.3"));
+        assert_eq!(matrix.as_of("2008-12-31"), vec![("2008-07-01", "14412.8")]);
+
+        let withdrawn: VintageMatrix = serde_json::from_str(r#"[
+            {"date": "2008-07-01", "GDP_20081030": "14546.7", "GDP_20081126": "."}
+        ]"#).unwrap();
+        assert_eq!(withdrawn.as_of("2008-12-31"), vec![("2008-07-01", "14546.7")]);
+        assert_eq!(withdrawn.first_releases()[0].value, "14546.7");
+    }
+}
@@ -0,0 +1,479 @@
+//! A watcher that polls the metadata of a watchlist and emits an event to each sink when a series
+//! changes. A change in `observation_end` means new observations; a change in `last_updated`
+//! alone means existing observations were revised.
+//!
+//! The watchlist, poll interval and sinks are read from a JSON config,
+//! ```json
+//! {
+//!     "watchlist": ["GDP", "UNRATE"],
+//!     "interval_secs": 600,
+//!     "state_path": "watch_state.json",
+//!     "sinks": [
+//!         {"type": "stdout"},
+//!         {"type": "json_lines", "path": "changes.jsonl"},
+//!         {"type": "webhook", "url": "http://localhost:8080/fred"}
+//!     ]
+//! }
+//! ```
+//! and the watcher is run with
+//! ```
+//! let mut watcher = Watcher::from_config(&WatchConfig::load("watch.json")?)?;
+//! watcher.run();
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use crate::{
+    mirror::fetch_item,
+    SeriesId,
+    SeriesItem,
+};
+
+/// The watcher config.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WatchConfig {
+    pub watchlist:                  Vec<SeriesId>,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs:              u64,
+    /// Where the last seen metadata is kept between runs. Without it, the first poll after a
+    /// restart emits no events.
+    #[serde(default)]
+    pub state_path:                 Option<PathBuf>,
+    #[serde(default)]
+    pub sinks:                      Vec<SinkConfig>,
+}
+
+fn default_interval_secs() -> u64 {
+    600
+}
+
+impl WatchConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<WatchConfig> {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path).context(format!("Failed to read [{}]", path.display()))?;
+        serde_json::from_str(&s).context(format!("Failed to parse [{}]", path.display()))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    Stdout,
+    JsonLines { path: PathBuf },
+    Webhook { url: String },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// `observation_end` moved.
+    NewObservations,
+    /// `last_updated` moved but `observation_end` did not.
+    Revision,
+}
+
+/// A change to a watched series.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChangeEvent {
+    pub series_id:                  SeriesId,
+    pub kind:                       ChangeKind,
+    pub title:                      String,
+    pub last_updated:               String,
+    pub previous_last_updated:      String,
+    pub observation_end:            String,
+    pub previous_observation_end:   String,
+    /// When the change was detected, in UTC.
+    pub detected_at:                String,
+}
+
+/// A destination for change events.
+pub trait Sink {
+    fn emit(&mut self, event: &ChangeEvent) -> Result<()>;
+}
+
+/// Prints each event as a line of JSON.
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn emit(&mut self, event: &ChangeEvent) -> Result<()> {
+        println!("{}", serde_json::to_string(event)?);
+        Ok(())
+    }
+}
+
+/// Appends each event to a file as a line of JSON.
+pub struct JsonLinesSink {
+    path: PathBuf,
+}
+
+impl JsonLinesSink {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        JsonLinesSink { path: path.as_ref().to_path_buf() }
+    }
+}
+
+impl Sink for JsonLinesSink {
+    fn emit(&mut self, event: &ChangeEvent) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context(format!("Failed to open [{}]", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(event)?)
+            .context(format!("Failed to write [{}]", self.path.display()))
+    }
+}
+
+/// POSTs each event as JSON to a URL.
+pub struct WebhookSink {
+    url:    String,
+    client: reqwest::blocking::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str) -> Self {
+        WebhookSink { url: url.to_string(), client: reqwest::blocking::Client::new() }
+    }
+}
+
+impl Sink for WebhookSink {
+    fn emit(&mut self, event: &ChangeEvent) -> Result<()> {
+        let response = self.client.post(&self.url).json(event).send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Webhook [{}] responded with [{}].", self.url, response.status()))
+        }
+        Ok(())
+    }
+}
+
+/// The metadata last seen for each watched series.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct WatchState {
+    pub seen:                       BTreeMap<SeriesId, SeenSeries>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SeenSeries {
+    pub last_updated:               String,
+    pub observation_end:            String,
+}
+
+/// The outcome of one poll.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WatchPoll {
+    pub events:                     Vec<ChangeEvent>,
+    /// Series whose metadata could not be fetched. They are retried on the next poll.
+    pub failed:                     Vec<(SeriesId, String)>,
+    /// Events a sink failed to deliver, with the position of the sink. The state of these series
+    /// is kept, so the change is detected again on the next poll and emitted to every sink,
+    /// including those that delivered it.
+    pub undelivered:                Vec<(usize, SeriesId, String)>,
+}
+
+type FetchItem = Box<dyn Fn(&SeriesId) -> Result<SeriesItem>>;
+
+/// Polls the metadata of a watchlist.
+pub struct Watcher {
+    watchlist:  Vec<SeriesId>,
+    interval:   Duration,
+    state_path: Option<PathBuf>,
+    state:      WatchState,
+    sinks:      Vec<Box<dyn Sink>>,
+    fetch:      FetchItem,
+}
+
+impl Watcher {
+    pub fn new(watchlist: Vec<SeriesId>) -> Self {
+        Watcher {
+            watchlist,
+            interval:   Duration::from_secs(default_interval_secs()),
+            state_path: None,
+            state:      WatchState::default(),
+            sinks:      Vec::new(),
+            fetch:      Box::new(fetch_item),
+        }
+    }
+
+    /// A watcher built from `config`, with state loaded from `state_path` if it exists.
+    pub fn from_config(config: &WatchConfig) -> Result<Self> {
+        let mut watcher = Watcher::new(config.watchlist.clone())
+            .interval(Duration::from_secs(config.interval_secs));
+        if let Some(path) = &config.state_path {
+            watcher = watcher.state_path(path)?;
+        }
+        for sink in &config.sinks {
+            watcher = match sink {
+                SinkConfig::Stdout => watcher.sink(StdoutSink),
+                SinkConfig::JsonLines { path } => watcher.sink(JsonLinesSink::new(path)),
+                SinkConfig::Webhook { url } => watcher.sink(WebhookSink::new(url)),
+            };
+        }
+        Ok(watcher)
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Keep state in `path`, loading it if it exists.
+    pub fn state_path<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            let s = fs::read_to_string(path).context(format!("Failed to read [{}]", path.display()))?;
+            self.state = serde_json::from_str(&s).context(format!("Failed to parse [{}]", path.display()))?;
+        }
+        self.state_path = Some(path.to_path_buf());
+        Ok(self)
+    }
+
+    pub fn sink<S: Sink + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    pub fn state(&self) -> &WatchState {
+        &self.state
+    }
+
+    /// Poll every series once, emit the events to every sink and save the state. Series seen for
+    /// the first time are recorded without an event. Fails only if the state cannot be saved.
+    pub fn poll(&mut self) -> Result<WatchPoll> {
+        let mut poll = WatchPoll::default();
+        for id in &self.watchlist {
+            let item = match (self.fetch)(id) {
+                Ok(item) => item,
+                Err(err) => {
+                    poll.failed.push((id.clone(), format!("{:#}", err)));
+                    continue
+                },
+            };
+            let seen = SeenSeries {
+                last_updated:       item.last_updated.clone(),
+                observation_end:    item.observation_end.clone(),
+            };
+            if let Some(previous) = self.state.seen.get(id) {
+                if previous.last_updated != seen.last_updated || previous.observation_end != seen.observation_end {
+                    let kind = if previous.observation_end != seen.observation_end {
+                        ChangeKind::NewObservations
+                    } else {
+                        ChangeKind::Revision
+                    };
+                    let event = ChangeEvent {
+                        series_id:                  id.clone(),
+                        kind,
+                        title:                      item.title.clone(),
+                        last_updated:               seen.last_updated.clone(),
+                        previous_last_updated:      previous.last_updated.clone(),
+                        observation_end:            seen.observation_end.clone(),
+                        previous_observation_end:   previous.observation_end.clone(),
+                        detected_at:                Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    };
+                    let undelivered = poll.undelivered.len();
+                    for (i, sink) in self.sinks.iter_mut().enumerate() {
+                        if let Err(err) = sink.emit(&event) {
+                            poll.undelivered.push((i, id.clone(), format!("{:#}", err)));
+                        }
+                    }
+                    poll.events.push(event);
+                    if poll.undelivered.len() > undelivered {
+                        continue
+                    }
+                }
+            }
+            self.state.seen.insert(id.clone(), seen);
+        }
+
+        if let Some(path) = &self.state_path {
+            fs::write(path, serde_json::to_string(&self.state)?)
+                .context(format!("Failed to write [{}]", path.display()))?;
+        }
+        Ok(poll)
+    }
+
+    /// Poll every `interval`, forever. Failures are written to stderr and the series involved
+    /// are polled again next time.
+    pub fn run(&mut self) -> ! {
+        loop {
+            match self.poll() {
+                Ok(poll) => {
+                    for (id, err) in &poll.failed {
+                        eprintln!("Failed to fetch [{}]: {}", id, err);
+                    }
+                    for (i, id, err) in &poll.undelivered {
+                        eprintln!("Sink [{}] failed to deliver [{}]: {}", i, id, err);
+                    }
+                },
+                Err(err) => eprintln!("Poll failed: {:#}", err),
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::RefCell,
+        io::{Read, Write},
+        net::TcpListener,
+        rc::Rc,
+        sync::mpsc,
+        thread,
+    };
+    use crate::{
+        ChangeEvent,
+        ChangeKind,
+        SeriesId,
+        SeriesItem,
+        Sink,
+        WatchConfig,
+        Watcher,
+        WebhookSink,
+    };
+
+    fn series_item(last_updated: &str, observation_end: &str) -> SeriesItem {
+        SeriesItem {
+            frequency:                  "Quarterly".into(),
+            last_updated:               last_updated.into(),
+            observation_end:            observation_end.into(),
+            ..crate::test::series_item("GDP")
+        }
+    }
+
+    #[test]
+    fn watcher_detects_new_observations_and_revisions() {
+        let dir = std::env::temp_dir().join(format!("fred_api_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let lines = dir.join("changes.jsonl");
+
+        let config: WatchConfig = serde_json::from_str(&format!(
+            r#"{{"watchlist": ["GDP"], "interval_secs": 1, "sinks": [{{"type": "json_lines", "path": {:?}}}]}}"#,
+            lines,
+        )).unwrap();
+        let mut watcher = Watcher::from_config(&config).unwrap();
+
+        let server = Rc::new(RefCell::new(series_item("2024-01-25 07:56:01-06", "2023-10-01")));
+        let s = server.clone();
+        watcher.fetch = Box::new(move |_| Ok(s.borrow().clone()));
+
+        assert!(watcher.poll().unwrap().events.is_empty());
+        *server.borrow_mut() = series_item("2024-02-28 07:56:01-06", "2023-10-01");
+        assert_eq!(watcher.poll().unwrap().events[0].kind, ChangeKind::Revision);
+        *server.borrow_mut() = series_item("2024-04-25 07:56:01-05", "2024-01-01");
+        assert_eq!(watcher.poll().unwrap().events[0].kind, ChangeKind::NewObservations);
+        assert!(watcher.poll().unwrap().events.is_empty());
+
+        let written: Vec<ChangeEvent> = std::fs::read_to_string(&lines)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(written.len(), 2);
+        assert_eq!(written[1].previous_observation_end, "2023-10-01");
+        assert_eq!(written[1].series_id, SeriesId::new("GDP").unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Records events, failing while `fail` is set.
+    struct FlakySink {
+        fail:       Rc<RefCell<bool>>,
+        received:   Rc<RefCell<Vec<ChangeEvent>>>,
+    }
+
+    impl Sink for FlakySink {
+        fn emit(&mut self, event: &ChangeEvent) -> anyhow::Result<()> {
+            if *self.fail.borrow() {
+                return Err(anyhow::anyhow!("Sink is down."))
+            }
+            self.received.borrow_mut().push(event.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failed_delivery_is_retried_on_next_poll() {
+        let server = Rc::new(RefCell::new(series_item("2024-01-25 07:56:01-06", "2023-10-01")));
+        let (fail, first, second) = Default::default();
+        let mut watcher = Watcher::new(vec![SeriesId::new("GDP").unwrap()])
+            .sink(FlakySink { fail: Rc::clone(&fail), received: Rc::clone(&first) })
+            .sink(FlakySink { fail: Rc::new(RefCell::new(false)), received: Rc::clone(&second) });
+        let s = server.clone();
+        watcher.fetch = Box::new(move |_| Ok(s.borrow().clone()));
+        assert!(watcher.poll().unwrap().events.is_empty());
+
+        // The first sink fails, the second still receives the event, and the state is kept.
+        *server.borrow_mut() = series_item("2024-02-28 07:56:01-06", "2024-01-01");
+        *fail.borrow_mut() = true;
+        let poll = watcher.poll().unwrap();
+        assert_eq!(poll.undelivered.len(), 1);
+        assert_eq!(poll.undelivered[0].0, 0);
+        assert_eq!(second.borrow().len(), 1);
+        assert_eq!(watcher.state().seen.values().next().unwrap().observation_end, "2023-10-01");
+
+        *fail.borrow_mut() = false;
+        let poll = watcher.poll().unwrap();
+        assert!(poll.undelivered.is_empty());
+        assert_eq!(first.borrow()[0].previous_observation_end, "2023-10-01");
+        assert_eq!(watcher.state().seen.values().next().unwrap().observation_end, "2024-01-01");
+        assert!(watcher.poll().unwrap().events.is_empty());
+    }
+
+    #[test]
+    fn webhook_posts_event_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/fred", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            // Read until the body, whose length is given by the Content-Length header, is complete.
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(split) = text.find("\r\n\r\n") {
+                    let length = text[..split]
+                        .lines()
+                        .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if text.len() >= split + 4 + length {
+                        tx.send(text).unwrap();
+                        break
+                    }
+                }
+                if n == 0 {
+                    break
+                }
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+        });
+
+        let event = ChangeEvent {
+            series_id:                  SeriesId::new("GDP").unwrap(),
+            kind:                       ChangeKind::NewObservations,
+            title:                      "Gross Domestic Product".into(),
+            last_updated:               "2024-04-25 07:56:01-05".into(),
+            previous_last_updated:      "2024-01-25 07:56:01-06".into(),
+            observation_end:            "2024-01-01".into(),
+            previous_observation_end:   "2023-10-01".into(),
+            detected_at:                "2024-04-25 13:00:00".into(),
+        };
+        WebhookSink::new(&url).emit(&event).unwrap();
+
+        let request = rx.recv().unwrap();
+        assert!(request.starts_with("POST /fred HTTP/1.1"));
+        let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+        assert_eq!(serde_json::from_str::<ChangeEvent>(body).unwrap(), event);
+    }
+}
@@ -0,0 +1,244 @@
+use fred_api::{
+    maps::{RegionalData, SeriesGroup},
+    Categories,
+    CategoryChildren,
+    CategoryRelated,
+    CategoryRelatedTags,
+    CategorySeries,
+    CategoryTags,
+    Release,
+    ReleaseDates,
+    ReleaseRelatedTags,
+    ReleaseSeries,
+    ReleaseSources,
+    ReleaseTables,
+    ReleaseTags,
+    Releases,
+    ReleasesDates,
+    Series,
+    SeriesCategories,
+    SeriesObservations,
+    SeriesRelease,
+    SeriesSearch,
+    SeriesSearchRelatedTags,
+    SeriesSearchTags,
+    SeriesTags,
+    SeriesUpdates,
+    SeriesVintageDates,
+    SourceReleases,
+    Sources,
+    Tags,
+    TagsSeries,
+    VintageObservations,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::fmt::Debug;
+
+// Every value in `serialized` must match the fixture, and every field of the fixture must be
+// serialized. Fields absent from the fixture may only serialize as null.
+fn assert_preserved(serialized: &Value, fixture: &Value, path: &str) {
+    match (serialized, fixture) {
+        (Value::Object(ser), Value::Object(fix)) => {
+            for (key, value) in ser {
+                let path = format!("{}.{}", path, key);
+                match fix.get(key) {
+                    Some(fixture_value) => assert_preserved(value, fixture_value, &path),
+                    None => assert!(value.is_null(), "[{}] not in fixture", path),
+                }
+            }
+            for key in fix.keys() {
+                assert!(ser.contains_key(key), "[{}.{}] dropped", path, key);
+            }
+        },
+        (Value::Array(ser), Value::Array(fix)) => {
+            assert_eq!(ser.len(), fix.len(), "[{}] length differs", path);
+            for (i, (s, f)) in ser.iter().zip(fix).enumerate() {
+                assert_preserved(s, f, &format!("{}[{}]", path, i));
+            }
+        },
+        // Integers in the fixture may come back as floats.
+        (Value::Number(ser), Value::Number(fix)) => {
+            assert_eq!(ser.as_f64(), fix.as_f64(), "[{}] differs", path)
+        },
+        // Ids FRED sends as text come back as numbers.
+        (Value::Number(ser), Value::String(fix)) => {
+            assert_eq!(ser.as_f64(), fix.parse().ok(), "[{}] differs", path)
+        },
+        _ => assert_eq!(serialized, fixture, "[{}] differs", path),
+    }
+}
+
+fn round_trip<T>(fixture: &str)
+where
+    T: DeserializeOwned + Serialize + PartialEq + Debug,
+{
+    let fixture_value: Value = serde_json::from_str(fixture).unwrap();
+    let response: T = serde_json::from_str(fixture).unwrap();
+
+    let serialized = serde_json::to_value(&response).unwrap();
+    assert_preserved(&serialized, &fixture_value, "");
+
+    let again: T = serde_json::from_value(serialized).unwrap();
+    assert_eq!(again, response);
+}
+
+#[test]
+fn series_round_trips() {
+    round_trip::<Series>(include_str!("fixtures/series.json"));
+}
+
+#[test]
+fn series_observations_round_trips() {
+    round_trip::<SeriesObservations>(include_str!("fixtures/series_observations.json"));
+}
+
+#[test]
+fn category_series_round_trips() {
+    round_trip::<CategorySeries>(include_str!("fixtures/category_series.json"));
+}
+
+#[test]
+fn categories_round_trips() {
+    round_trip::<Categories>(include_str!("fixtures/categories.json"));
+}
+
+#[test]
+fn releases_round_trips() {
+    round_trip::<Releases>(include_str!("fixtures/releases.json"));
+}
+
+#[test]
+fn release_dates_round_trips() {
+    round_trip::<ReleaseDates>(include_str!("fixtures/release_dates.json"));
+}
+
+#[test]
+fn series_tags_round_trips() {
+    round_trip::<SeriesTags>(include_str!("fixtures/series_tags.json"));
+}
+
+#[test]
+fn series_vintagedates_round_trips() {
+    round_trip::<SeriesVintageDates>(include_str!("fixtures/series_vintagedates.json"));
+}
+
+#[test]
+fn vintage_observations_round_trips() {
+    round_trip::<VintageObservations>(include_str!("fixtures/series_observations_vintage.json"));
+}
+
+#[test]
+fn category_children_round_trips() {
+    round_trip::<CategoryChildren>(include_str!("fixtures/category_children.json"));
+}
+
+#[test]
+fn category_related_round_trips() {
+    round_trip::<CategoryRelated>(include_str!("fixtures/category_related.json"));
+}
+
+#[test]
+fn category_tags_round_trips() {
+    round_trip::<CategoryTags>(include_str!("fixtures/category_tags.json"));
+}
+
+#[test]
+fn category_related_tags_round_trips() {
+    round_trip::<CategoryRelatedTags>(include_str!("fixtures/category_related_tags.json"));
+}
+
+#[test]
+fn release_round_trips() {
+    round_trip::<Release>(include_str!("fixtures/release.json"));
+}
+
+#[test]
+fn releases_dates_round_trips() {
+    round_trip::<ReleasesDates>(include_str!("fixtures/releases_dates.json"));
+}
+
+#[test]
+fn release_series_round_trips() {
+    round_trip::<ReleaseSeries>(include_str!("fixtures/release_series.json"));
+}
+
+#[test]
+fn release_sources_round_trips() {
+    round_trip::<ReleaseSources>(include_str!("fixtures/release_sources.json"));
+}
+
+#[test]
+fn release_tags_round_trips() {
+    round_trip::<ReleaseTags>(include_str!("fixtures/release_tags.json"));
+}
+
+#[test]
+fn release_related_tags_round_trips() {
+    round_trip::<ReleaseRelatedTags>(include_str!("fixtures/release_related_tags.json"));
+}
+
+#[test]
+fn release_tables_round_trips() {
+    round_trip::<ReleaseTables>(include_str!("fixtures/release_tables.json"));
+}
+
+#[test]
+fn series_categories_round_trips() {
+    round_trip::<SeriesCategories>(include_str!("fixtures/series_categories.json"));
+}
+
+#[test]
+fn series_release_round_trips() {
+    round_trip::<SeriesRelease>(include_str!("fixtures/series_release.json"));
+}
+
+#[test]
+fn series_search_round_trips() {
+    round_trip::<SeriesSearch>(include_str!("fixtures/series_search.json"));
+}
+
+#[test]
+fn series_search_tags_round_trips() {
+    round_trip::<SeriesSearchTags>(include_str!("fixtures/series_search_tags.json"));
+}
+
+#[test]
+fn series_search_related_tags_round_trips() {
+    round_trip::<SeriesSearchRelatedTags>(include_str!("fixtures/series_search_related_tags.json"));
+}
+
+#[test]
+fn series_updates_round_trips() {
+    round_trip::<SeriesUpdates>(include_str!("fixtures/series_updates.json"));
+}
+
+#[test]
+fn sources_round_trips() {
+    round_trip::<Sources>(include_str!("fixtures/sources.json"));
+}
+
+#[test]
+fn source_releases_round_trips() {
+    round_trip::<SourceReleases>(include_str!("fixtures/source_releases.json"));
+}
+
+#[test]
+fn tags_round_trips() {
+    round_trip::<Tags>(include_str!("fixtures/tags.json"));
+}
+
+#[test]
+fn tags_series_round_trips() {
+    round_trip::<TagsSeries>(include_str!("fixtures/tags_series.json"));
+}
+
+#[test]
+fn maps_regional_data_round_trips() {
+    round_trip::<RegionalData>(include_str!("fixtures/maps_regional_data.json"));
+}
+
+#[test]
+fn maps_series_group_round_trips() {
+    round_trip::<SeriesGroup>(include_str!("fixtures/maps_series_group.json"));
+}
@@ -1,15 +1,45 @@
 use fred_api::{
     Format,
     FredClient,
+    SeriesId,
 };
+use serde_json::Map;
+use std::{fs, path::Path};
 
 #[test]
 fn series_tags_request_works() {
-    let series_tags = FredClient::series_tags("JPNCPIALLMINMEI").unwrap();
+    let series_tags = FredClient::series_tags(&SeriesId::new("JPNCPIALLMINMEI").unwrap()).unwrap();
     assert!(!series_tags.realtime_start.is_empty());
     assert_eq!(series_tags.order_by, "series_count");
 }
 
+// Writes `tests/fixtures/transformations/<series_id>.json` for `tests/transformations.rs`: the
+// series metadata and the observations FRED returns for each `units` value. Needs FRED_API_KEY and
+// network access, so run it with `cargo test --test test -- --ignored capture`.
+#[test]
+#[ignore]
+fn capture_transformation_fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/transformations");
+    fs::create_dir_all(&dir).unwrap();
+    for id in &["GDP", "CPIAUCSL", "ICSA"] {
+        let mut fixture = Map::new();
+        fixture.insert("series".into(), FredClient::raw("series", &[("series_id", *id)]).unwrap());
+        for code in &["lin", "chg", "ch1", "pch", "pc1", "pca", "cch", "cca", "log"] {
+            let observations = FredClient::raw(
+                "series/observations",
+                &[
+                    ("series_id", *id),
+                    ("units", code),
+                    ("observation_start", "2015-01-01"),
+                    ("observation_end", "2019-12-31"),
+                ],
+            ).unwrap();
+            fixture.insert(code.to_string(), observations);
+        }
+        fs::write(dir.join(format!("{}.json", id)), serde_json::to_string_pretty(&fixture).unwrap()).unwrap();
+    }
+}
+
 // #[test]
 // fn category() {
 //     Fred::category(1);
@@ -0,0 +1,47 @@
+//! Local transformations against the observations FRED returns for each `units` value, captured
+//! by `capture_transformation_fixtures` in `tests/test.rs`.
+
+use fred_api::{Frequency, Series, SeriesObservations, TimeSeries, Transformation};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+// A quarterly, a monthly and a weekly series.
+const SERIES: [&str; 3] = ["GDP", "CPIAUCSL", "ICSA"];
+
+fn observations(fixture: &Value, code: &str) -> TimeSeries {
+    let observations: SeriesObservations = serde_json::from_value(fixture[code].clone()).unwrap();
+    TimeSeries::from_observations(&observations).unwrap()
+}
+
+#[test]
+fn transformations_match_fred() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/transformations");
+    for id in &SERIES {
+        let path = dir.join(format!("{}.json", id));
+        if !path.exists() {
+            eprintln!("[{}] has not been captured.", path.display());
+            continue
+        }
+        let fixture: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let series: Series = serde_json::from_value(fixture["series"].clone()).unwrap();
+        let frequency = Frequency::from(series.seriess.iter().next().unwrap().frequency.as_str());
+        let level = observations(&fixture, "lin");
+
+        for code in &["chg", "ch1", "pch", "pc1", "pca", "cch", "cca", "log"] {
+            let expected = observations(&fixture, code);
+            let transformation: Transformation = code.parse().unwrap();
+            let actual = level.transform_with_frequency(transformation, &frequency).unwrap();
+            assert_eq!(actual.dates(), expected.dates(), "{} {}", id, code);
+            for ((date, a), (_, e)) in actual.iter().zip(expected.iter()) {
+                match (a, e) {
+                    // FRED rounds transformed values.
+                    (Some(a), Some(e)) => assert!(
+                        (a - e).abs() <= 1e-3 * e.abs().max(1.0),
+                        "{} {} {}: {} != {}", id, code, date, a, e,
+                    ),
+                    (a, e) => assert_eq!(a, e, "{} {} {}", id, code, date),
+                }
+            }
+        }
+    }
+}